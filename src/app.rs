@@ -2,69 +2,92 @@ use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifier
 use ratatui::{
   buffer::Buffer,
   layout::Rect,
+  text::Text,
   widgets::{Block, Paragraph, Widget},
   DefaultTerminal,
 };
-use std::{
-  env,
-  io::{self, Read},
-};
+use std::{io, time::Duration};
 
 use crate::{
   help::{draw_help, HELP_LINES},
+  hexview::HexView,
   info::get_detail_string,
-  loader::load,
-  pcapng::PngBlock,
+  packet_list::PacketList,
+  stats::Stats,
+  tree::TreeView,
 };
-use crate::{hexview::HexView, pcapng::parse};
+use hexng::{capture::Capture, loader::load_config};
+
+// Blocks added to the statistics between redraws, so that the interface stays responsive
+const STATS_BATCH: usize = 20_000;
+// Width of the statistics pane, including its border
+const STATS_WIDTH: u16 = 50;
+// Width of the section tree pane, including its border
+const TREE_WIDTH: u16 = 50;
+// Height of the block list pane, including its border and header
+const LIST_HEIGHT: u16 = 12;
 
 pub struct App {
-  data: Vec<Box<dyn PngBlock>>,
+  data: Capture,
   hexview: HexView,
   exit: bool,
-  path: std::path::PathBuf,
+  // Shown in the header bar
+  name: String,
   help: bool,
+  stats: Stats,
+  // Whether the statistics pane is shown
+  show_stats: bool,
+  tree: TreeView,
+  // Whether the section tree pane is shown
+  show_tree: bool,
+  list: PacketList,
+  // Whether the block list pane is shown
+  show_list: bool,
 }
 
 impl App {
-  pub fn new(path: std::path::PathBuf) -> std::io::Result<App> {
-    let f = std::fs::File::open(&path).expect("Failed to open file");
-    let mut reader = std::io::BufReader::new(f);
-    let mut raw: Vec<u8> = vec![];
-    let mut config = load(
-      env::current_exe()
-        .unwrap()
-        .parent()
-        .unwrap()
-        .join("data.json")
-        .to_str()
-        .unwrap(),
-    )?;
-    let local_config = load(
-      env::current_dir()
-        .unwrap()
-        .join(".hexng.json")
-        .to_str()
-        .unwrap(),
-    );
-    if local_config.is_ok() {
-      let mut c = local_config.unwrap();
-      config.enhanced_packets.append(&mut c.enhanced_packets);
-      config.link_types.extend(c.link_types);
+  // Reads from stdin when there is no path
+  pub fn new(path: Option<std::path::PathBuf>, resync: bool, follow: bool) -> std::io::Result<App> {
+    let data = Capture::load(path.as_deref(), load_config()?, resync, follow)?;
+    let mut name = path.as_ref().map_or("stdin".to_owned(), |p| {
+      p.file_name()
+        .unwrap_or(p.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+    });
+    if let Some(compression) = data.compression() {
+      name += &format!(" ({})", compression.name());
     }
-    reader.read_to_end(&mut raw)?;
+    let mut hexview = HexView::default();
+    hexview.tail = follow;
     let application = App {
-      data: parse(&raw, config),
-      hexview: HexView::default(),
+      data,
+      hexview,
       exit: false,
-      path,
+      name,
       help: false,
+      stats: Stats::default(),
+      show_stats: false,
+      tree: TreeView::default(),
+      show_tree: false,
+      list: PacketList::default(),
+      show_list: false,
     };
     Ok(application)
   }
 
   pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
     while !self.exit {
+      self.data.receive();
+      if let Some(id) = self.data.take_rescanned() {
+        self.hexview.forget(id);
+        if self.stats.counted > id as usize {
+          self.stats = Stats::default();
+        }
+      }
+      if self.show_stats {
+        self.stats.update(&self.data, STATS_BATCH);
+      }
       terminal.draw(|frame| self.draw(frame.area(), frame.buffer_mut()))?;
       self.handle_events()?;
     }
@@ -72,6 +95,11 @@ impl App {
   }
 
   fn handle_events(&mut self) -> io::Result<()> {
+    // Keep redrawing while blocks are still being indexed or counted
+    let counting = self.show_stats && self.stats.counted < self.data.block_count();
+    if (self.data.scanning() || counting) && !event::poll(Duration::from_millis(100))? {
+      return Ok(());
+    }
     match event::read()? {
       Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
         self.handle_key_event(key_event)
@@ -82,26 +110,47 @@ impl App {
   }
 
   fn handle_key_event(&mut self, key_event: KeyEvent) {
+    let in_tree = self.show_tree && self.tree.focused;
+    let in_list = self.show_list && self.list.focused;
+    let count = self.data.block_count();
+    let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
     match key_event.code {
       KeyCode::Char('q') => self.exit = true,
+      KeyCode::Char('j') if in_list => self.list.down(count),
+      KeyCode::Char('k') if in_list => self.list.up(),
+      KeyCode::Char('G') if in_list => self.list.bottom(count),
+      KeyCode::Char('g') if in_list => self.list.top(count),
+      KeyCode::Char('d') if in_list && control => self.list.down_half(count),
+      KeyCode::Char('u') if in_list && control => self.list.up_half(),
+      KeyCode::Char('j') if in_tree => self.tree.down(),
+      KeyCode::Char('k') if in_tree => self.tree.up(),
+      KeyCode::Char('h') if in_tree => self.tree.collapse(),
+      KeyCode::Char('l') if in_tree => self.tree.expand(),
+      KeyCode::Enter if in_tree => self.tree.toggle(),
       KeyCode::Char('j') => self.hexview.down(),
       KeyCode::Char('k') => self.hexview.up(),
       KeyCode::Char('h') => self.hexview.left(),
       KeyCode::Char('l') => self.hexview.right(),
       KeyCode::Char('G') => self.hexview.bottom(),
       KeyCode::Char('g') => self.hexview.top(),
-      KeyCode::Char('d') => {
-        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
-          self.hexview.down_half()
-        }
-      }
-      KeyCode::Char('u') => {
-        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
-          self.hexview.up_half()
-        }
-      }
+      KeyCode::Char('d') if control => self.hexview.down_half(),
+      KeyCode::Char('u') if control => self.hexview.up_half(),
       KeyCode::Char('f') => self.hexview.fold(),
       KeyCode::Char('a') => self.hexview.toggle_ascii(),
+      KeyCode::Char('F') => {
+        self.data.set_following(!self.data.following());
+        self.hexview.tail = self.data.following();
+      }
+      KeyCode::Char('s') => self.show_stats = !self.show_stats,
+      KeyCode::Char('p') => {
+        self.show_list = !self.show_list;
+        self.list.focused = false;
+      }
+      KeyCode::Char('t') => {
+        self.show_tree = !self.show_tree;
+        self.tree.focused = false;
+      }
+      KeyCode::Tab => self.next_focus(),
       KeyCode::Char('?') => self.toggle_help(),
       _ => (),
     }
@@ -109,14 +158,22 @@ impl App {
 
   fn draw(&mut self, area: Rect, buf: &mut Buffer) {
     Paragraph::new(
-      self
-        .path
-        .file_name()
-        .expect("Error") // TODO: Error handling
-        .to_str()
-        .unwrap_or("Invalid unicode in path")
-        .to_owned()
-        + std::format!(" | {} Packets", self.data.len()).as_str(),
+      self.name.clone()
+        + std::format!(
+          " | {} Sections | {} Packets",
+          self.data.section_count(),
+          self.data.block_count()
+        )
+        .as_str()
+        + &match self.data.progress() {
+          Some(percent) => format!(
+            " | {} blocks parsed / {}%",
+            self.data.block_count(),
+            percent
+          ),
+          None if self.data.following() => " | Following".to_owned(),
+          None => "".to_owned(),
+        },
     )
     .block(Block::bordered())
     .render(
@@ -138,11 +195,62 @@ impl App {
       buf,
     );
 
-    let hex_area = Rect {
+    let mut hex_area = Rect {
       y: area.y + 6,
       height: area.height - 6,
       ..area
     };
+    let mut list_area = None;
+    if self.show_list {
+      let height = LIST_HEIGHT.min(hex_area.height / 2);
+      list_area = Some(Rect { height, ..hex_area });
+      hex_area.y += height;
+      hex_area.height -= height;
+    }
+    if self.show_stats {
+      let width = STATS_WIDTH.min(area.width / 2);
+      hex_area.width -= width;
+      let title = if self.stats.counted < self.data.block_count() {
+        format!(
+          "Statistics ({} of {} blocks)",
+          self.stats.counted,
+          self.data.block_count()
+        )
+      } else {
+        "Statistics".to_owned()
+      };
+      Paragraph::new(Text::from_iter(self.stats.lines()))
+        .block(Block::bordered().title(title))
+        .render(
+          Rect {
+            x: hex_area.x + hex_area.width,
+            width,
+            ..hex_area
+          },
+          buf,
+        );
+    }
+    let mut tree_area = None;
+    if self.show_tree {
+      let width = TREE_WIDTH.min(hex_area.width / 2);
+      hex_area.width -= width;
+      tree_area = Some(Rect {
+        x: hex_area.x + hex_area.width,
+        width,
+        ..hex_area
+      });
+    }
+    // Selecting in the tree marks the bytes, otherwise the hex cursor marks them
+    self.hexview.highlight = self.tree.highlight().filter(|_| self.tree.focused);
+    // Moving in the list scrolls the hex view, otherwise the list follows the hex cursor
+    let under_cursor = self.hexview.id_under_cursor().map(|(id, _)| id);
+    if let Some(id) = self
+      .list
+      .selected
+      .filter(|&id| self.list.focused && under_cursor != Some(id))
+    {
+      self.hexview.show_block(id);
+    }
     Block::bordered().render(hex_area, buf);
     self.hexview.draw(
       Rect {
@@ -152,17 +260,31 @@ impl App {
         height: hex_area.height - 2,
       },
       buf,
-      &self.data,
+      &mut self.data,
     );
 
-    let (id, cursor_y) = self.hexview.id_under_cursor();
-    Paragraph::new(get_detail_string(
-      &self.data[id as usize],
-      hex_area.width - 2,
-      (self.hexview.cursor.0, cursor_y),
-    ))
-    .block(Block::bordered())
-    .render(
+    if let Some(list_area) = list_area {
+      if !self.list.focused {
+        self.list.selected = self.hexview.id_under_cursor().map(|(id, _)| id);
+      }
+      self.list.draw(list_area, buf, &self.data);
+    }
+
+    let under_cursor = self.hexview.id_under_cursor().map(|(id, _)| id);
+    let offset = self.hexview.cursor_offset();
+    if let Some(tree_area) = tree_area {
+      self.tree.update(
+        under_cursor.and_then(|id| Some((id, self.data.block(id)?.sections()))),
+        offset,
+      );
+      self.tree.draw(tree_area, buf);
+    }
+
+    let detail = match under_cursor.and_then(|id| self.data.block(id)) {
+      Some(block) => get_detail_string(block, offset),
+      None => "".to_owned(),
+    };
+    Paragraph::new(detail).block(Block::bordered()).render(
       Rect {
         height: 3,
         width: area.width,
@@ -173,18 +295,34 @@ impl App {
     );
 
     if self.help {
+      // Centred, and cut down to fit a small terminal
       draw_help(
         Rect {
-          x: (area.width - 40) / 2,
-          y: (area.height - HELP_LINES) / 2,
+          x: area.width.saturating_sub(40) / 2,
+          y: area.height.saturating_sub(HELP_LINES + 2) / 2,
           width: 40,
           height: HELP_LINES + 2,
-        },
+        }
+        .clamp(area),
         buf,
       );
     }
   }
 
+  // Hands the keys on to the next pane shown, going from the hex view to the list to the tree
+  fn next_focus(&mut self) {
+    if self.list.focused {
+      self.list.focused = false;
+      self.tree.focused = self.show_tree;
+    } else if self.tree.focused {
+      self.tree.focused = false;
+    } else if self.show_list {
+      self.list.focused = true;
+    } else {
+      self.tree.focused = self.show_tree;
+    }
+  }
+
   fn toggle_help(&mut self) {
     self.help = !self.help;
   }
@@ -1,46 +1,159 @@
-use crate::pcapng::{BlockErrorKind, PngBlock};
+use crate::options::has_end_of_options;
+use crate::pcapng::{BlockErrorKind, PngBlock, SectionContext};
+use crate::section::{Section, Value};
 use crate::types::{block_type_str, BlockTypes};
-use crate::util::div_ceil;
+use crate::util::read_u32;
 
 pub struct BaseBlock {
   pub id_: u32,
+  pub section_: u32,
   pub raw_: Vec<u8>,
   pub block_type_: BlockTypes,
   pub length_: u32,
   pub options_: Vec<u8>,
   pub error_: BlockErrorKind,
+  // Every problem found, including ones after the first that error_ holds
+  pub errors_: Vec<BlockErrorKind>,
+  pub little_endian_: bool,
 }
 
 impl BaseBlock {
   pub const SIZE: usize = 12;
 
-  pub fn parse(data: &[u8], id: u32) -> (BaseBlock, usize) {
-    let block_type: BlockTypes = u32::from_le_bytes(data[..4].try_into().unwrap()).into();
-    let length = u32::from_le_bytes(data[4..8].try_into().unwrap());
+  // Checks that the block's length fields can be trusted to find the next block
+  pub fn check(data: &[u8], little_endian: bool) -> BlockErrorKind {
+    if data.len() < 8 {
+      return BlockErrorKind::Truncated;
+    }
+    let length = read_u32(&data[4..8], little_endian) as usize;
     if length == 0 {
-      return (
-        BaseBlock::new(
-          vec![],
-          block_type,
-          length,
-          vec![],
-          id,
-          BlockErrorKind::ZeroLength,
-        ),
-        0,
-      );
+      BlockErrorKind::ZeroLength
+    } else if length > data.len() {
+      BlockErrorKind::Truncated
+    } else if length < Self::SIZE {
+      BlockErrorKind::LengthMismatch
+    } else if !length.is_multiple_of(4) {
+      BlockErrorKind::Misaligned
+    } else if read_u32(&data[length - 4..length], little_endian) as usize != length {
+      BlockErrorKind::TrailingLengthMismatch
+    } else {
+      BlockErrorKind::None
     }
-    return (
+  }
+
+  pub fn parse(data: &[u8], id: u32, section: &SectionContext) -> (BaseBlock, usize) {
+    let little_endian = section.little_endian;
+    let error = Self::check(data, little_endian);
+    let block_type: BlockTypes = match data.get(..4) {
+      Some(b) => read_u32(b, little_endian).into(),
+      None => BlockTypes::Unknown,
+    };
+    let length = Self::framed_length(data, error, little_endian);
+    (
       BaseBlock::new(
-        data[..(length as usize)].to_vec(),
+        data[..length].to_vec(),
         block_type,
-        length,
+        length as u32,
         vec![],
         id,
-        BlockErrorKind::None,
+        error,
+        section,
       ),
-      length as usize,
-    );
+      length,
+    )
+  }
+
+  // Number of bytes a block occupies given the result of check
+  pub fn framed_length(data: &[u8], error: BlockErrorKind, little_endian: bool) -> usize {
+    match error {
+      BlockErrorKind::ZeroLength => 0,
+      // Show everything that is left
+      BlockErrorKind::Truncated => data.len(),
+      // Only the type and length fields are meaningful
+      BlockErrorKind::LengthMismatch => 8,
+      _ => read_u32(&data[4..8], little_endian) as usize,
+    }
+  }
+
+  // Rows needed to draw a block of the given length, including its title
+  pub fn row_count(length: usize, width: u16) -> u16 {
+    if length == 0 {
+      return 1;
+    }
+    let bytes_in_row = (width as usize + 1) / 3;
+    let rows = length.div_ceil(bytes_in_row) + 1;
+    rows.min(u16::MAX as usize) as u16
+  }
+
+  // Sets an error that does not stop the block from being decoded, keeping any earlier one
+  pub fn flag(&mut self, error: BlockErrorKind) {
+    if self.error_ == BlockErrorKind::None {
+      self.error_ = error;
+    }
+    self.errors_.push(error);
+  }
+
+  // Puts a block's body between its type and length fields
+  pub fn frame(&self, body: Vec<Section>) -> Vec<Section> {
+    let mut sections = self.sections();
+    sections.splice(2..3, body);
+    Section::place(&mut sections, 0, self.little_endian_);
+    sections
+  }
+
+  fn framing_sections(&self) -> Vec<Section> {
+    let length = self.raw_.len();
+    if matches!(
+      self.block_type_,
+      BlockTypes::PcapFileHeader | BlockTypes::PcapRecord
+    ) {
+      return vec![Section::new("Data", length, Value::None)];
+    }
+    let block_type = self
+      .raw_
+      .get(..4)
+      .map_or(0, |b| read_u32(b, self.little_endian_));
+    let mut sections = vec![Section::new(
+      "Block Type",
+      std::cmp::min(length, 4),
+      Value::Enum(block_type as u64, block_type_str(&self.block_type_)),
+    )];
+    if length < 8 {
+      if length > 4 {
+        sections.push(Section::new("Data", length - 4, Value::None));
+      }
+      return sections;
+    }
+    let declared_length = read_u32(&self.raw_[4..8], self.little_endian_);
+    sections.push(Section::new(
+      "Block Length",
+      4,
+      Value::Uint(declared_length as u64),
+    ));
+    if self.error_ == BlockErrorKind::Truncated || length < Self::SIZE {
+      if length > 8 {
+        sections.push(Section::new("Data", length - 8, Value::None));
+      }
+      return sections;
+    }
+    sections.push(Section::new("Data", length - Self::SIZE, Value::None));
+    let trailing_length = read_u32(&self.raw_[length - 4..], self.little_endian_);
+    let trailing = Section::new("Block Length", 4, Value::Uint(trailing_length as u64));
+    sections.push(if trailing_length != declared_length {
+      trailing.as_invalid()
+    } else {
+      trailing
+    });
+    sections
+  }
+
+  // Flags an options area running from start to the trailing length that is not closed by
+  // opt_endofopt
+  pub fn check_options(&mut self, start: usize) {
+    let end = self.length_ as usize - 4;
+    if start < end && !has_end_of_options(&self.raw_[start..end], self.little_endian_) {
+      self.flag(BlockErrorKind::MissingEndOfOptions);
+    }
   }
 }
 
@@ -52,6 +165,7 @@ impl BaseBlock {
     options: Vec<u8>,
     id: u32,
     error: BlockErrorKind,
+    section: &SectionContext,
   ) -> BaseBlock {
     BaseBlock {
       raw_: raw,
@@ -59,34 +173,26 @@ impl BaseBlock {
       length_: length,
       options_: options,
       id_: id,
+      section_: section.index,
       error_: error,
+      errors_: if error == BlockErrorKind::None {
+        vec![]
+      } else {
+        vec![error]
+      },
+      little_endian_: section.little_endian,
     }
   }
 }
 
 impl PngBlock for BaseBlock {
   fn rows(&self, width: u16) -> u16 {
-    if self.error_ == BlockErrorKind::ZeroLength {
-      return 1;
-    }
-    let bytes_in_row = (width + 1) / 3;
-    div_ceil(self.length_ as u16, bytes_in_row) + 1
+    Self::row_count(self.length_ as usize, width)
   }
 
-  fn sections(&self) -> Vec<(String, usize)> {
-    let sections = vec![
-      (
-        "Block Type - ".to_owned() + &block_type_str(&self.block_type_),
-        4,
-      ),
-      ("Block Length - ".to_owned() + &self.length_.to_string(), 4),
-      ("Data".to_owned(), self.length_ as usize - Self::SIZE),
-      ("Block Length - ".to_owned() + &self.length_.to_string(), 4),
-    ];
-    assert_eq!(
-      sections.iter().map(|s| s.1 as u32).sum::<u32>(),
-      self.length_
-    );
+  fn sections(&self) -> Vec<Section> {
+    let mut sections = self.framing_sections();
+    Section::place(&mut sections, 0, self.little_endian_);
     sections
   }
 
@@ -94,12 +200,25 @@ impl PngBlock for BaseBlock {
     &self.error_
   }
 
+  fn errors(&self) -> &[BlockErrorKind] {
+    &self.errors_
+  }
+
   fn id(&self) -> u32 {
     self.id_
   }
 
+  fn section(&self) -> u32 {
+    self.section_
+  }
+
   fn title_line(&self) -> String {
-    self.id_.to_string() + ": " + &block_type_str(self.block_type())
+    format!(
+      "{} (Section {}): {}",
+      self.id_,
+      self.section_,
+      block_type_str(self.block_type())
+    )
   }
 
   fn length(&self) -> usize {
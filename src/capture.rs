@@ -0,0 +1,408 @@
+use std::{
+  collections::HashMap,
+  fs::File,
+  io::{self, Read},
+  path::Path,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{self, Receiver, Sender, TryRecvError},
+    Arc,
+  },
+  thread::{self, JoinHandle},
+  time::Duration,
+};
+
+use memmap2::Mmap;
+
+use crate::{
+  baseblock::BaseBlock,
+  compression::{decompress, Compression},
+  loader::Config,
+  pcapng::{decode, BlockEntry, PngBlock, Scanner, SectionContext},
+};
+
+// Decoded blocks kept around for redrawing, enough for several screens
+const CACHE_SIZE: usize = 1024;
+// Entries sent from the scanning thread at a time
+const BATCH_SIZE: usize = 4096;
+// How often a followed file is checked for new data
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
+// Entries indexed by the scanning thread since its last batch
+struct Batch {
+  // Map of the file at the time the entries were scanned, which covers all of them
+  data: Arc<Mmap>,
+  entries: Vec<BlockEntry>,
+  scanned: usize,
+}
+
+// A memory mapped capture file, indexed by block and decoded as blocks are looked at
+pub struct Capture {
+  data: Arc<Mmap>,
+  config: Arc<Config>,
+  entries: Vec<BlockEntry>,
+  // Each block with the time it was last looked at, counted in calls to block
+  cache: HashMap<u32, (u64, Box<dyn PngBlock>)>,
+  uses: u64,
+  // Receives from the scanning thread until it finishes
+  receiver: Option<Receiver<Batch>>,
+  // Returns the scanner from before it gave up on any block cut short at the end of the file
+  scan: Option<JoinHandle<Scanner>>,
+  scanned: usize,
+  // First block replaced by scanning again since the last call to take_rescanned
+  rescanned: Option<u32>,
+  // Set while the file is being written, so that the scan waits for the blocks at its end
+  writing: Arc<AtomicBool>,
+  // A mapped file, which unlike a stream can be followed again after its scan has finished
+  file: Option<File>,
+  compression: Option<Compression>,
+}
+
+impl Capture {
+  // Opens a file, or stdin if there is no path, decompressing it if needed
+  pub fn load(
+    path: Option<&Path>,
+    config: Config,
+    resync: bool,
+    follow: bool,
+  ) -> io::Result<Capture> {
+    let (stream, compression) = match path {
+      Some(path) => decompress(File::open(path)?)?,
+      None => decompress(io::stdin())?,
+    };
+    let mut capture = match (path, compression) {
+      // Uncompressed files are mapped rather than copied
+      (Some(path), None) => Self::open(path, config, resync, follow)?,
+      // Reopened so that the decompressor waits for more data instead of stopping at the end
+      (Some(path), Some(compression)) if follow => {
+        let stream = compression.decoder(Tail(File::open(path)?))?;
+        Self::read(stream, config, resync)?
+      }
+      // Streams are already read until they end, so following them needs nothing more
+      _ => Self::read(stream, config, resync)?,
+    };
+    capture.compression = compression;
+    Ok(capture)
+  }
+
+  // Maps the file and starts indexing it in the background, then keeps watching it for new
+  // blocks if following
+  pub fn open(path: &Path, config: Config, resync: bool, follow: bool) -> io::Result<Capture> {
+    // Following goes on until it is stopped, as there is no way to tell when the writer is done
+    let file = File::open(path)?;
+    let mut capture = Self::start(
+      file.try_clone()?,
+      config,
+      resync,
+      Arc::new(AtomicBool::new(follow)),
+    )?;
+    capture.file = Some(file);
+    Ok(capture)
+  }
+
+  // Copies a stream such as a pipe into an anonymous temporary file, indexing it as it arrives
+  pub fn read<R: Read + Send + 'static>(
+    mut reader: R,
+    config: Config,
+    resync: bool,
+  ) -> io::Result<Capture> {
+    let mut file = tempfile::tempfile()?;
+    let scan_file = file.try_clone()?;
+    let writing = Arc::new(AtomicBool::new(true));
+    let copy_writing = writing.clone();
+    thread::spawn(move || {
+      // A read error ends the capture just like the end of the stream
+      let _ = io::copy(&mut reader, &mut file);
+      copy_writing.store(false, Ordering::Release);
+    });
+    Self::start(scan_file, config, resync, writing)
+  }
+
+  // Indexes the file on a background thread. While writing is set, blocks cut short at the end
+  // of the file are waited for rather than reported as truncated
+  fn start(
+    file: File,
+    config: Config,
+    resync: bool,
+    writing: Arc<AtomicBool>,
+  ) -> io::Result<Capture> {
+    // The map is only read, and the file is assumed not to shrink while it is open
+    let data = Arc::new(unsafe { Mmap::map(&file)? });
+    let mut capture = Capture {
+      data,
+      config: Arc::new(config),
+      entries: vec![],
+      cache: HashMap::new(),
+      uses: 0,
+      receiver: None,
+      scan: None,
+      scanned: 0,
+      rescanned: None,
+      writing,
+      file: None,
+      compression: None,
+    };
+    capture.spawn(file, Scanner::new(resync, true));
+    Ok(capture)
+  }
+
+  fn spawn(&mut self, file: File, scanner: Scanner) {
+    let (sender, receiver) = mpsc::channel();
+    let data = self.data.clone();
+    let config = self.config.clone();
+    let writing = self.writing.clone();
+    self.receiver = Some(receiver);
+    self.scan = Some(thread::spawn(move || {
+      scan(file, data, config, scanner, writing, sender)
+    }));
+  }
+
+  // Starts or stops watching a mapped file for new blocks
+  pub fn set_following(&mut self, follow: bool) {
+    if self.file.is_none() {
+      return;
+    }
+    self.writing.store(follow, Ordering::Release);
+    if follow && self.receiver.is_none() {
+      self.resume();
+    }
+  }
+
+  // Scans again from the end of the last complete block, replacing anything found cut short after
+  // it now that more of it may have been written
+  fn resume(&mut self) {
+    let Some(Ok(scanner)) = self.scan.take().map(JoinHandle::join) else {
+      return;
+    };
+    let Some(Ok(file)) = self.file.as_ref().map(File::try_clone) else {
+      return;
+    };
+    let count = self.entries.partition_point(|e| e.offset < scanner.pos());
+    self.entries.truncate(count);
+    self.cache.retain(|&id, _| (id as usize) < count);
+    self.rescanned = Some(
+      self
+        .rescanned
+        .map_or(count as u32, |id| id.min(count as u32)),
+    );
+    self.scanned = scanner.pos();
+    self.spawn(file, scanner);
+  }
+
+  // Takes whatever the scanning thread has indexed since the last call
+  pub fn receive(&mut self) {
+    while let Some(receiver) = &self.receiver {
+      match receiver.try_recv() {
+        Ok(batch) => self.append(batch),
+        Err(TryRecvError::Empty) => break,
+        Err(TryRecvError::Disconnected) => {
+          self.receiver = None;
+          // Following was started again while the scan was finishing
+          if self.following_requested() {
+            self.resume();
+          }
+        }
+      }
+    }
+  }
+
+  // Blocks until the whole file is indexed, which never happens if following
+  pub fn wait(&mut self) {
+    while let Some(receiver) = &self.receiver {
+      match receiver.recv() {
+        Ok(batch) => self.append(batch),
+        Err(_) => self.receiver = None,
+      }
+    }
+  }
+
+  fn append(&mut self, mut batch: Batch) {
+    self.data = batch.data;
+    self.entries.append(&mut batch.entries);
+    self.scanned = batch.scanned;
+  }
+
+  // Percentage of the file indexed, or None once indexing has caught up with the file
+  pub fn progress(&self) -> Option<u8> {
+    self.receiver.as_ref()?;
+    if self.scanned >= self.data.len() {
+      return None;
+    }
+    Some((self.scanned * 100 / self.data.len()) as u8)
+  }
+
+  // First block that has been replaced since the last call, so that anything worked out from the
+  // blocks after it can be thrown away
+  pub fn take_rescanned(&mut self) -> Option<u32> {
+    self.rescanned.take()
+  }
+
+  // Whether the scanning thread may still send blocks
+  pub fn scanning(&self) -> bool {
+    self.receiver.is_some()
+  }
+
+  // Whether the file is being watched for new blocks
+  pub fn following(&self) -> bool {
+    self.receiver.is_some() && self.writing.load(Ordering::Acquire)
+  }
+
+  fn following_requested(&self) -> bool {
+    self.file.is_some() && self.writing.load(Ordering::Acquire)
+  }
+
+  pub fn compression(&self) -> Option<Compression> {
+    self.compression
+  }
+
+  pub fn block_count(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn section_count(&self) -> u32 {
+    self.entries.last().map_or(0, |e| e.section.index + 1)
+  }
+
+  // Position of a block in the file, after any decompression
+  pub fn offset(&self, id: u32) -> Option<usize> {
+    Some(self.entries.get(id as usize)?.offset)
+  }
+
+  // Rows a block takes up when drawn, without decoding it
+  pub fn rows(&self, id: u32, width: u16) -> Option<u16> {
+    Some(BaseBlock::row_count(
+      self.entries.get(id as usize)?.length,
+      width,
+    ))
+  }
+
+  // Section state a block was scanned with
+  pub fn context(&self, id: u32) -> Option<&SectionContext> {
+    Some(&self.entries.get(id as usize)?.section)
+  }
+
+  pub fn config(&self) -> &Config {
+    &self.config
+  }
+
+  pub fn block(&mut self, id: u32) -> Option<&dyn PngBlock> {
+    self.uses += 1;
+    if !self.cache.contains_key(&id) {
+      let block = self.decode(id)?;
+      if self.cache.len() >= CACHE_SIZE {
+        // Drop the least recently used block, which will not be on screen
+        let oldest = self.cache.iter().min_by_key(|(_, (used, _))| *used);
+        if let Some(oldest) = oldest.map(|(&id, _)| id) {
+          self.cache.remove(&oldest);
+        }
+      }
+      self.cache.insert(id, (self.uses, block));
+    }
+    let (used, block) = self.cache.get_mut(&id)?;
+    *used = self.uses;
+    Some(&**block)
+  }
+
+  // Decodes a block without caching it, for passes over the whole file that would otherwise push
+  // out the blocks on screen
+  pub fn decode(&self, id: u32) -> Option<Box<dyn PngBlock>> {
+    decode(&self.data, self.entries.get(id as usize)?, id, &self.config)
+  }
+}
+
+// A file that is still being written, read as if it never ends
+struct Tail(File);
+
+impl Read for Tail {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+      match self.0.read(buf)? {
+        0 if !buf.is_empty() => thread::sleep(FOLLOW_INTERVAL),
+        length => return Ok(length),
+      }
+    }
+  }
+}
+
+// Indexes the file, sending entries back in batches, and rescans it as it grows while it is
+// being written. Returns the scanner as it was before giving up on any block cut short at the
+// end of the file, so that it can carry on if the file is followed again.
+fn scan(
+  file: File,
+  mut data: Arc<Mmap>,
+  config: Arc<Config>,
+  mut scanner: Scanner,
+  writing: Arc<AtomicBool>,
+  sender: Sender<Batch>,
+) -> Scanner {
+  loop {
+    // Checked before mapping, so that the last pass sees everything that was written
+    let finished = !writing.load(Ordering::Acquire);
+    match file.metadata() {
+      Ok(metadata) if metadata.len() as usize > data.len() => match unsafe { Mmap::map(&file) } {
+        Ok(map) => data = Arc::new(map),
+        Err(_) => return scanner,
+      },
+      Ok(_) => (),
+      Err(_) => return scanner,
+    }
+    let mut entries = vec![];
+    if !index(&mut scanner, &data, &config, &mut entries, &sender) {
+      // The capture has been closed
+      return scanner;
+    }
+    let complete = scanner.clone();
+    if finished {
+      scanner.stop_following();
+      if !index(&mut scanner, &data, &config, &mut entries, &sender) {
+        return complete;
+      }
+    }
+    // Caught up with the file, apart from any block that is still being written
+    let batch = Batch {
+      data: data.clone(),
+      entries,
+      scanned: data.len(),
+    };
+    if sender.send(batch).is_err() || finished {
+      return complete;
+    }
+    wait_for_growth(&file, data.len(), &writing);
+  }
+}
+
+// Adds the entries the scanner finds to entries, sending them on whenever there is a full batch.
+// Returns false if nothing is receiving them any more.
+fn index(
+  scanner: &mut Scanner,
+  data: &Arc<Mmap>,
+  config: &Config,
+  entries: &mut Vec<BlockEntry>,
+  sender: &Sender<Batch>,
+) -> bool {
+  while let Some(entry) = scanner.next(data, config) {
+    entries.push(entry);
+    if entries.len() == BATCH_SIZE {
+      let batch = Batch {
+        data: data.clone(),
+        entries: std::mem::take(entries),
+        scanned: scanner.pos(),
+      };
+      if sender.send(batch).is_err() {
+        return false;
+      }
+    }
+  }
+  true
+}
+
+// Polls until the file is longer than length or the writer has finished
+fn wait_for_growth(file: &File, length: usize, writing: &AtomicBool) {
+  loop {
+    thread::sleep(FOLLOW_INTERVAL);
+    let grown = file.metadata().is_ok_and(|m| m.len() as usize > length);
+    if grown || !writing.load(Ordering::Acquire) {
+      return;
+    }
+  }
+}
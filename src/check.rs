@@ -0,0 +1,42 @@
+use std::io::{self, Write};
+
+use hexng::{capture::Capture, pcapng::BlockErrorKind, types::block_type_str};
+
+// Writes every structural problem in the capture, one per line with the block it was found in and
+// the block's file offset, followed by a summary. Returns the number of problems.
+pub fn check(capture: &mut Capture, out: &mut impl Write) -> io::Result<usize> {
+  let mut count = 0;
+  for id in 0..capture.block_count() as u32 {
+    let (Some(offset), Some(block)) = (capture.offset(id), capture.block(id)) else {
+      continue;
+    };
+    // An unknown link type only means data.json has no fields for it
+    for error in block
+      .errors()
+      .iter()
+      .filter(|e| **e != BlockErrorKind::UnknownLinkType)
+    {
+      writeln!(
+        out,
+        "block {} at {:#010x} ({}): {}",
+        id,
+        offset,
+        block_type_str(block.block_type()),
+        error.description()
+      )?;
+      count += 1;
+    }
+  }
+  let plural = |n: usize| if n == 1 { "" } else { "s" };
+  let blocks = capture.block_count();
+  writeln!(
+    out,
+    "{} problem{} found in {} block{}",
+    count,
+    plural(count),
+    blocks,
+    plural(blocks)
+  )?;
+  out.flush()?;
+  Ok(count)
+}
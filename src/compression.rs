@@ -0,0 +1,127 @@
+use std::io::{self, Read};
+
+use flate2::read::MultiGzDecoder;
+use lz4_flex::frame::FrameDecoder;
+use ruzstd::decoding::StreamingDecoder;
+use xz2::read::XzDecoder;
+
+// Longest magic number below
+const MAGIC_LENGTH: u64 = 6;
+
+#[derive(Clone, Copy)]
+pub enum Compression {
+  Gzip,
+  Zstd,
+  Xz,
+  Lz4,
+}
+
+impl Compression {
+  pub fn detect(data: &[u8]) -> Option<Compression> {
+    if data.starts_with(&[0x1f, 0x8b]) {
+      Some(Compression::Gzip)
+    } else if data.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+      Some(Compression::Zstd)
+    } else if data.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+      Some(Compression::Xz)
+    } else if data.starts_with(&[0x04, 0x22, 0x4d, 0x18]) {
+      Some(Compression::Lz4)
+    } else {
+      None
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Compression::Gzip => "gzip",
+      Compression::Zstd => "zstd",
+      Compression::Xz => "xz",
+      Compression::Lz4 => "lz4",
+    }
+  }
+
+  pub fn decoder<R: Read + Send + 'static>(&self, reader: R) -> io::Result<Box<dyn Read + Send>> {
+    Ok(match self {
+      Compression::Gzip => Box::new(MultiGzDecoder::new(reader)),
+      Compression::Zstd => Box::new(StreamingDecoder::new(reader).map_err(io::Error::other)?),
+      Compression::Xz => Box::new(XzDecoder::new_multi_decoder(reader)),
+      Compression::Lz4 => Box::new(FrameDecoder::new(reader)),
+    })
+  }
+}
+
+// Reads enough of a stream to recognise its compression, and returns the whole stream,
+// decompressed if it needs to be
+pub fn decompress<R: Read + Send + 'static>(
+  mut reader: R,
+) -> io::Result<(Box<dyn Read + Send>, Option<Compression>)> {
+  let mut magic = vec![];
+  (&mut reader).take(MAGIC_LENGTH).read_to_end(&mut magic)?;
+  let compression = Compression::detect(&magic);
+  let stream = io::Cursor::new(magic).chain(reader);
+  Ok(match compression {
+    Some(compression) => (compression.decoder(stream)?, Some(compression)),
+    None => (Box::new(stream), None),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::Write;
+
+  use super::*;
+
+  const CAPTURE: &[u8] = b"\x0a\x0d\x0d\x0a capture data that compresses well well well well";
+
+  fn read(data: Vec<u8>) -> (Vec<u8>, Option<&'static str>) {
+    let (mut stream, compression) = decompress(io::Cursor::new(data)).unwrap();
+    let mut out = vec![];
+    stream.read_to_end(&mut out).unwrap();
+    (out, compression.map(|c| c.name()))
+  }
+
+  fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+  }
+
+  #[test]
+  fn uncompressed() {
+    assert_eq!(read(CAPTURE.to_vec()), (CAPTURE.to_vec(), None));
+    // Shorter than the longest magic number
+    assert_eq!(read(vec![0x1f]), (vec![0x1f], None));
+    assert_eq!(read(vec![]), (vec![], None));
+  }
+
+  #[test]
+  fn gzip_members() {
+    assert_eq!(read(gzip(CAPTURE)), (CAPTURE.to_vec(), Some("gzip")));
+    // Members appended one after another are read as a single stream
+    let (first, second) = CAPTURE.split_at(10);
+    assert_eq!(
+      read([gzip(first), gzip(second)].concat()),
+      (CAPTURE.to_vec(), Some("gzip"))
+    );
+  }
+
+  #[test]
+  fn other_formats() {
+    let zstd =
+      ruzstd::encoding::compress_to_vec(CAPTURE, ruzstd::encoding::CompressionLevel::Fastest);
+    assert_eq!(read(zstd), (CAPTURE.to_vec(), Some("zstd")));
+    let mut xz = xz2::write::XzEncoder::new(vec![], 6);
+    xz.write_all(CAPTURE).unwrap();
+    assert_eq!(read(xz.finish().unwrap()), (CAPTURE.to_vec(), Some("xz")));
+    let mut lz4 = lz4_flex::frame::FrameEncoder::new(vec![]);
+    lz4.write_all(CAPTURE).unwrap();
+    assert_eq!(read(lz4.finish().unwrap()), (CAPTURE.to_vec(), Some("lz4")));
+  }
+
+  #[test]
+  fn truncated_compressed_stream() {
+    let data = gzip(CAPTURE);
+    let (mut stream, _) = decompress(io::Cursor::new(data[..data.len() / 2].to_vec())).unwrap();
+    assert!(stream.read_to_end(&mut vec![]).is_err());
+  }
+}
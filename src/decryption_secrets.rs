@@ -0,0 +1,213 @@
+use crate::{
+  baseblock::BaseBlock,
+  options::option_sections,
+  pcapng::{BlockErrorKind, PngBlock, SectionContext},
+  section::{Section, Value},
+  types::BlockTypes,
+  util::read_u32,
+};
+
+pub struct DecryptionSecrets {
+  base: BaseBlock,
+  secrets_type: u32,
+  secrets_length: u32,
+  sections_: Vec<Section>,
+}
+
+fn secrets_type_str(secrets_type: u32) -> &'static str {
+  match secrets_type {
+    0x544c534b => "TLS Key Log",
+    0x5353484b => "SSH Key Log",
+    0x57474b4c => "WireGuard Key Log",
+    0x5a4e574b => "ZigBee NWK Key",
+    0x5a415053 => "ZigBee APS Key",
+    0x55414b4c => "OPC UA Key Log",
+    _ => "Unknown",
+  }
+}
+
+impl DecryptionSecrets {
+  pub const SIZE: usize = BaseBlock::SIZE + 8;
+
+  pub fn parse(
+    data: &[u8],
+    id: u32,
+    section: &SectionContext,
+  ) -> Result<(DecryptionSecrets, usize), BlockErrorKind> {
+    let little_endian = section.little_endian;
+    let mut base = BaseBlock::parse(data, id, section);
+    if (base.0.length_ as usize) < Self::SIZE {
+      return Err(BlockErrorKind::LengthMismatch);
+    }
+    let secrets_type = read_u32(&data[8..12], little_endian);
+    let secrets_length = read_u32(&data[12..16], little_endian);
+    if Self::SIZE + (secrets_length as usize).div_ceil(4) * 4 > base.0.length_ as usize {
+      return Err(BlockErrorKind::LengthMismatch);
+    }
+    base
+      .0
+      .check_options(Self::SIZE + (secrets_length as usize).div_ceil(4) * 4 - 4);
+    let mut p = DecryptionSecrets {
+      base: base.0,
+      secrets_type,
+      secrets_length,
+      sections_: vec![],
+    };
+    p.sections_impl();
+    if p.sections_.iter().map(|s| s.length).sum::<usize>() != p.length() {
+      return Err(BlockErrorKind::LengthMismatch);
+    }
+    Ok((p, base.1))
+  }
+
+  fn is_key_log(&self) -> bool {
+    matches!(
+      self.secrets_type,
+      0x544c534b | 0x5353484b | 0x57474b4c | 0x55414b4c
+    )
+  }
+
+  fn sections_impl(&mut self) {
+    let mut sections = vec![
+      Section::new(
+        "Secrets Type",
+        4,
+        Value::Enum(
+          self.secrets_type as u64,
+          secrets_type_str(self.secrets_type).to_owned(),
+        ),
+      ),
+      Section::new("Secrets Length", 4, Value::Uint(self.secrets_length as u64)),
+    ];
+    let start = Self::SIZE - 4;
+    let end = start + self.secrets_length as usize;
+    let secrets = &self.raw()[start..end];
+    if self.is_key_log() {
+      // One section per line so the detail pane shows the line under the cursor
+      let lines = secrets
+        .split_inclusive(|&b| b == b'\n')
+        .map(|line| {
+          let text = String::from_utf8_lossy(line).trim_end().to_owned();
+          Section::new("Key Log", line.len(), Value::String(text)).as_text()
+        })
+        .collect();
+      sections.push(Section::group("Secrets", Value::None, lines));
+    } else if !secrets.is_empty() {
+      sections.push(Section::new("Secrets", secrets.len(), Value::None));
+    }
+    let padding = (4 - self.secrets_length as usize % 4) % 4;
+    if padding > 0 {
+      sections.push(Section::new("Padding", padding, Value::None));
+    }
+    sections.extend(option_sections(
+      &self.raw()[end + padding..self.length() - 4],
+      self.block_type(),
+      self.base.little_endian_,
+      None,
+    ));
+    self.sections_ = self.base.frame(sections);
+  }
+}
+
+impl PngBlock for DecryptionSecrets {
+  fn rows(&self, width: u16) -> u16 {
+    self.base.rows(width)
+  }
+
+  fn sections(&self) -> Vec<Section> {
+    self.sections_.clone()
+  }
+
+  fn error(&self) -> &BlockErrorKind {
+    self.base.error()
+  }
+
+  fn errors(&self) -> &[BlockErrorKind] {
+    self.base.errors()
+  }
+
+  fn id(&self) -> u32 {
+    self.base.id()
+  }
+
+  fn section(&self) -> u32 {
+    self.base.section()
+  }
+
+  fn length(&self) -> usize {
+    self.base.length()
+  }
+
+  fn block_type(&self) -> &BlockTypes {
+    &BlockTypes::DecryptionSecretsBlock
+  }
+
+  fn title_line(&self) -> String {
+    self.base.title_line() + " - " + secrets_type_str(self.secrets_type)
+  }
+
+  fn raw(&self) -> &Vec<u8> {
+    self.base.raw()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Decryption Secrets Block holding secrets of the given type, with no options
+  fn block(secrets_type: u32, secrets: &[u8]) -> Vec<u8> {
+    let mut body = [
+      secrets_type.to_le_bytes(),
+      (secrets.len() as u32).to_le_bytes(),
+    ]
+    .concat();
+    body.extend(secrets);
+    body.resize(body.len().next_multiple_of(4), 0);
+    let length = (body.len() as u32 + 12).to_le_bytes();
+    [&10u32.to_le_bytes()[..], &length, &body, &length].concat()
+  }
+
+  fn parse(data: &[u8]) -> DecryptionSecrets {
+    DecryptionSecrets::parse(data, 0, &SectionContext::new(0, true))
+      .ok()
+      .unwrap()
+      .0
+  }
+
+  #[test]
+  fn key_log_lines() {
+    let secrets = b"CLIENT_RANDOM aa bb\r\nCLIENT_RANDOM cc dd\nlast";
+    let block = parse(&block(0x544c534b, secrets));
+    assert_eq!(*block.error(), BlockErrorKind::None);
+    let sections = block.sections();
+    let lines = &sections[4].children;
+    let values: Vec<_> = lines.iter().map(|l| l.value.to_string()).collect();
+    assert_eq!(
+      values,
+      ["CLIENT_RANDOM aa bb", "CLIENT_RANDOM cc dd", "last"]
+    );
+    let lengths: Vec<_> = lines.iter().map(|l| l.length).collect();
+    assert_eq!(lengths, [21, 20, 4]);
+    assert!(lines.iter().all(|l| l.text));
+    assert_eq!(sections[5].name, "Padding");
+    assert_eq!(sections[5].length, 3);
+  }
+
+  #[test]
+  fn other_secrets() {
+    let block = parse(&block(0x5a4e574b, &[1; 16]));
+    let sections = block.sections();
+    assert_eq!(sections[2].value.to_string(), "ZigBee NWK Key");
+    assert_eq!(sections[4].name, "Secrets");
+    assert!(sections[4].children.is_empty());
+  }
+
+  #[test]
+  fn secrets_longer_than_block() {
+    let mut data = block(0x544c534b, b"abcd");
+    data[12] = 200;
+    let result = DecryptionSecrets::parse(&data, 0, &SectionContext::new(0, true));
+    assert_eq!(result.err(), Some(BlockErrorKind::LengthMismatch));
+  }
+}
@@ -0,0 +1,120 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use hexng::{
+  capture::Capture,
+  pcapng::{to_hex, BlockErrorKind},
+  section::{Section, Value},
+  types::block_type_str,
+};
+
+#[derive(Serialize)]
+struct JsonBlock {
+  id: u32,
+  #[serde(rename = "type")]
+  block_type: String,
+  offset: usize,
+  length: usize,
+  error: Option<String>,
+  sections: Vec<JsonSection>,
+}
+
+#[derive(Serialize)]
+struct JsonSection {
+  name: String,
+  offset: usize,
+  length: usize,
+  value: Option<String>,
+  raw: String,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  children: Vec<JsonSection>,
+}
+
+impl JsonSection {
+  fn new(section: &Section, offset: usize, raw: &[u8]) -> JsonSection {
+    JsonSection {
+      name: section.name.clone(),
+      offset: offset + section.offset,
+      length: section.length,
+      value: (section.value != Value::None).then(|| section.value.to_string()),
+      raw: section
+        .raw(raw)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect(),
+      children: section
+        .children
+        .iter()
+        .map(|c| JsonSection::new(c, offset, raw))
+        .collect(),
+    }
+  }
+}
+
+// Writes each section that is not made up of others, and each group with a value of its own
+// before the sections that make it up
+fn write_sections(
+  out: &mut impl Write,
+  sections: &[Section],
+  offset: usize,
+  raw: &[u8],
+) -> io::Result<()> {
+  for section in sections {
+    if section.children.is_empty() || section.adds_value() {
+      writeln!(
+        out,
+        "  {:#010x} {:>6}  {} | {}",
+        offset + section.offset,
+        section.length,
+        section.description(),
+        to_hex(section.raw(raw))
+      )?;
+    }
+    write_sections(out, &section.children, offset, raw)?;
+  }
+  Ok(())
+}
+
+// Writes each block's title followed by its sections, one per line with file offset, length,
+// description and bytes
+pub fn dump_text(capture: &mut Capture, out: &mut impl Write) -> io::Result<()> {
+  for id in 0..capture.block_count() as u32 {
+    let (Some(offset), Some(block)) = (capture.offset(id), capture.block(id)) else {
+      continue;
+    };
+    write!(out, "{}", block.title_line())?;
+    if *block.error() != BlockErrorKind::None {
+      write!(out, " - ERROR {}", block.error().description())?;
+    }
+    writeln!(out)?;
+    write_sections(out, &block.sections(), offset, block.raw())?;
+  }
+  out.flush()
+}
+
+// Writes one JSON object per line for each block, with the name and value of each section and
+// the sections that make it up
+pub fn dump_json(capture: &mut Capture, out: &mut impl Write) -> io::Result<()> {
+  for id in 0..capture.block_count() as u32 {
+    let (Some(offset), Some(block)) = (capture.offset(id), capture.block(id)) else {
+      continue;
+    };
+    let sections = block
+      .sections()
+      .iter()
+      .map(|s| JsonSection::new(s, offset, block.raw()))
+      .collect();
+    let json_block = JsonBlock {
+      id,
+      block_type: block_type_str(block.block_type()),
+      offset,
+      length: block.length(),
+      error: (*block.error() != BlockErrorKind::None).then(|| format!("{:?}", block.error())),
+      sections,
+    };
+    serde_json::to_writer(&mut *out, &json_block)?;
+    writeln!(out)?;
+  }
+  out.flush()
+}
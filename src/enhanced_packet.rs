@@ -1,19 +1,28 @@
 use crate::{
   baseblock::BaseBlock,
   loader::Config,
-  pcapng::{BlockErrorKind, PngBlock},
+  options::option_sections,
+  packet_data::{check_lengths, packet_data_section, resolve_link_type, timestamp_section},
+  pcapng::{BlockErrorKind, PacketInfo, PngBlock, SectionContext},
+  section::{Section, Value},
   types::BlockTypes,
+  util::read_u32,
 };
 
+// Decodes the four bytes before the timestamp into the interface ID and the sections they are
+// shown as, the only part of the layout that differs between Enhanced and legacy Packet Blocks
+pub type IdFields = fn(&[u8], bool) -> (u32, Vec<Section>);
+
 pub struct EnhancedPacket {
   base: BaseBlock,
   interface_id: u32,
+  id_sections: Vec<Section>,
   timestamp_upper: u32,
   timestamp_lower: u32,
   captured_packet_length: u32,
   original_packet_length: u32,
-  link_type: u16,
-  sections_: Vec<(String, usize)>,
+  link_type: Option<u16>,
+  sections_: Vec<Section>,
   link_type_str: String,
 }
 
@@ -23,20 +32,59 @@ impl EnhancedPacket {
   pub fn parse(
     data: &[u8],
     id: u32,
-    interfaces: &Vec<u16>,
+    section: &SectionContext,
+    config: &Config,
+  ) -> Result<(EnhancedPacket, usize), BlockErrorKind> {
+    Self::parse_with(data, id, section, config, |field, little_endian| {
+      let interface_id = read_u32(field, little_endian);
+      (
+        interface_id,
+        vec![Section::new(
+          "Interface ID",
+          4,
+          Value::Uint(interface_id as u64),
+        )],
+      )
+    })
+  }
+
+  // Parses any block laid out like an Enhanced Packet Block, reading the fields before the
+  // timestamp with id_fields
+  pub fn parse_with(
+    data: &[u8],
+    id: u32,
+    section: &SectionContext,
     config: &Config,
-  ) -> (EnhancedPacket, usize) {
-    let interface_id = u32::from_le_bytes(data[8..12].try_into().unwrap());
-    let base = BaseBlock::parse(data, id);
-    let timestamp_upper = u32::from_le_bytes(data[12..16].try_into().unwrap());
-    let timestamp_lower = u32::from_le_bytes(data[16..20].try_into().unwrap());
-    let captured_packet_length = u32::from_le_bytes(data[24..28].try_into().unwrap());
-    let original_packet_length = u32::from_le_bytes(data[28..32].try_into().unwrap());
-    let link_type = interfaces[interface_id as usize];
-    let link_type_str = config.link_types[&link_type].clone();
+    id_fields: IdFields,
+  ) -> Result<(EnhancedPacket, usize), BlockErrorKind> {
+    let little_endian = section.little_endian;
+    let mut base = BaseBlock::parse(data, id, section);
+    if (base.0.length_ as usize) < Self::SIZE {
+      return Err(BlockErrorKind::LengthMismatch);
+    }
+    let (interface_id, id_sections) = id_fields(&data[8..12], little_endian);
+    let timestamp_upper = read_u32(&data[12..16], little_endian);
+    let timestamp_lower = read_u32(&data[16..20], little_endian);
+    let captured_packet_length = read_u32(&data[20..24], little_endian);
+    let original_packet_length = read_u32(&data[24..28], little_endian);
+    // Packet data is padded to 32 bits before the options start
+    let padded_length = (captured_packet_length as usize).div_ceil(4) * 4;
+    if Self::SIZE + padded_length > base.0.length_ as usize {
+      return Err(BlockErrorKind::LengthMismatch);
+    }
+    let (link_type, link_type_str) = resolve_link_type(interface_id, section, config, &mut base.0);
+    check_lengths(
+      interface_id,
+      captured_packet_length,
+      original_packet_length,
+      section,
+      &mut base.0,
+    );
+    base.0.check_options(Self::SIZE - 4 + padded_length);
     let mut p = EnhancedPacket {
       base: base.0,
       interface_id,
+      id_sections,
       timestamp_upper,
       timestamp_lower,
       captured_packet_length,
@@ -45,71 +93,51 @@ impl EnhancedPacket {
       sections_: vec![],
       link_type_str,
     };
-    p.sections_ = p.sections_impl(config);
-    (p, base.1)
+    p.sections_ = p.sections_impl(section, config);
+    if p.sections_.iter().map(|s| s.length).sum::<usize>() != p.length() {
+      return Err(BlockErrorKind::LengthMismatch);
+    }
+    Ok((p, base.1))
   }
 
-  fn sections_impl(&self, config: &Config) -> Vec<(String, usize)> {
-    let mut sections: Vec<(String, usize)> = vec![
-      (
-        "Interface ID - ".to_owned() + &self.interface_id.to_string(),
-        4,
+  fn sections_impl(&self, section: &SectionContext, config: &Config) -> Vec<Section> {
+    let mut sections = self.id_sections.clone();
+    sections.extend([
+      timestamp_section(
+        "Timestamp",
+        self.timestamp_upper,
+        self.timestamp_lower,
+        section.interface(self.interface_id).as_ref(),
       ),
-      (
-        "Timestamp Upper - ".to_owned() + &self.timestamp_upper.to_string(),
-        4,
-      ),
-      (
-        "Timestamp Lower - ".to_owned() + &self.timestamp_lower.to_string(),
+      Section::new(
+        "Captured Packet Length",
         4,
+        Value::Uint(self.captured_packet_length as u64),
       ),
-      (
-        "Captured Packet Length - ".to_owned() + &self.captured_packet_length.to_string(),
+      Section::new(
+        "Original Packet Length",
         4,
+        Value::Uint(self.original_packet_length as u64),
       ),
-      (
-        "Original Packet Length - ".to_owned() + &self.original_packet_length.to_string(),
-        4,
+      packet_data_section(
+        &self.raw()[Self::SIZE - 4..Self::SIZE - 4 + self.captured_packet_length as usize],
+        self.link_type,
+        section,
+        config,
       ),
-    ];
-
-    let mut sum = 0;
-    for en in &config.enhanced_packets {
-      let l: u16 = en.linktype.try_into().unwrap();
-      if l != self.link_type {
-        continue;
-      }
-      for s in &en.sections {
-        let data: &[u8] = &self.raw()[28 + sum..28 + sum + s.1];
-        let mut data_padded: [u8; 8] = [0; 8];
-        for i in 0..data.len().min(8) {
-          data_padded[i] = data[i];
-        }
-        sections.push((
-          s.0.clone() + " - " + &u64::from_le_bytes(data_padded).to_string(),
-          s.1,
-        ));
-        sum += s.1;
-      }
-      break;
+    ]);
+    let padding = (4 - self.captured_packet_length as usize % 4) % 4;
+    if padding > 0 {
+      sections.push(Section::new("Padding", padding, Value::None));
     }
-    sections.push((
-      "Data".to_owned(),
-      self.captured_packet_length as usize - sum,
-    ));
-    sections.push((
-      "Options".to_owned(),
-      self.length() - Self::SIZE - self.captured_packet_length as usize,
+    let options_start = Self::SIZE - 4 + self.captured_packet_length as usize + padding;
+    sections.extend(option_sections(
+      &self.raw()[options_start..self.length() - 4],
+      self.block_type(),
+      self.base.little_endian_,
+      None,
     ));
-    let mut base_sections = self.base.sections();
-    base_sections.remove(2);
-    base_sections.splice(2..2, sections);
-
-    assert_eq!(
-      base_sections.iter().map(|s| s.1).sum::<usize>(),
-      self.length()
-    );
-    base_sections
+    self.base.frame(sections)
   }
 }
 
@@ -118,7 +146,7 @@ impl PngBlock for EnhancedPacket {
     self.base.rows(width)
   }
 
-  fn sections(&self) -> Vec<(String, usize)> {
+  fn sections(&self) -> Vec<Section> {
     self.sections_.clone()
   }
 
@@ -126,16 +154,24 @@ impl PngBlock for EnhancedPacket {
     self.base.error()
   }
 
+  fn errors(&self) -> &[BlockErrorKind] {
+    self.base.errors()
+  }
+
   fn id(&self) -> u32 {
     self.base.id()
   }
 
+  fn section(&self) -> u32 {
+    self.base.section()
+  }
+
   fn length(&self) -> usize {
     self.base.length()
   }
 
   fn block_type(&self) -> &BlockTypes {
-    &BlockTypes::EnhancedPacketBlock
+    self.base.block_type()
   }
 
   fn title_line(&self) -> String {
@@ -145,4 +181,13 @@ impl PngBlock for EnhancedPacket {
   fn raw(&self) -> &Vec<u8> {
     self.base.raw()
   }
+
+  fn packet(&self) -> Option<PacketInfo> {
+    Some(PacketInfo {
+      interface_id: self.interface_id,
+      timestamp: Some(((self.timestamp_upper as u64) << 32) | self.timestamp_lower as u64),
+      captured_length: self.captured_packet_length,
+      original_length: self.original_packet_length,
+    })
+  }
 }
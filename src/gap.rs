@@ -0,0 +1,89 @@
+use crate::{
+  baseblock::BaseBlock,
+  pcapng::{BlockErrorKind, PngBlock, SectionContext},
+  section::{Section, Value},
+  types::{block_type_str, BlockTypes},
+  util::read_u32,
+};
+
+// Bytes skipped over while looking for the next valid block after a corrupt one
+pub struct Gap {
+  base: BaseBlock,
+}
+
+impl Gap {
+  pub fn new(data: &[u8], id: u32, error: BlockErrorKind, section: &SectionContext) -> Gap {
+    Gap {
+      base: BaseBlock::new(
+        data.to_vec(),
+        BlockTypes::UnparsedGap,
+        data.len() as u32,
+        vec![],
+        id,
+        error,
+        section,
+      ),
+    }
+  }
+}
+
+impl PngBlock for Gap {
+  fn rows(&self, width: u16) -> u16 {
+    self.base.rows(width)
+  }
+
+  fn sections(&self) -> Vec<Section> {
+    let length = self.length();
+    let little_endian = self.base.little_endian_;
+    let mut sections = if length < 8 {
+      vec![Section::new("Unparsed", length, Value::None)]
+    } else {
+      // Show what the corrupt block claimed to be
+      let block_type = read_u32(&self.raw()[..4], little_endian);
+      let block_length = read_u32(&self.raw()[4..8], little_endian);
+      vec![
+        Section::new(
+          "Block Type",
+          4,
+          Value::Enum(block_type as u64, block_type_str(&block_type.into())),
+        ),
+        Section::new("Block Length", 4, Value::Uint(block_length as u64)),
+        Section::new("Unparsed", length - 8, Value::None),
+      ]
+    };
+    Section::place(&mut sections, 0, little_endian);
+    sections
+  }
+
+  fn error(&self) -> &BlockErrorKind {
+    self.base.error()
+  }
+
+  fn errors(&self) -> &[BlockErrorKind] {
+    self.base.errors()
+  }
+
+  fn id(&self) -> u32 {
+    self.base.id()
+  }
+
+  fn section(&self) -> u32 {
+    self.base.section()
+  }
+
+  fn length(&self) -> usize {
+    self.base.length()
+  }
+
+  fn block_type(&self) -> &BlockTypes {
+    &BlockTypes::UnparsedGap
+  }
+
+  fn title_line(&self) -> String {
+    format!("{} - {} bytes", self.base.title_line(), self.length())
+  }
+
+  fn raw(&self) -> &Vec<u8> {
+    self.base.raw()
+  }
+}
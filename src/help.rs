@@ -17,13 +17,19 @@ const HELP_TEXT: &[&str] = &[
   "CTRL-U : Scroll up half page\n",
   "f      : Toggle fold\n",
   "a      : Toggle ascii\n",
+  "F      : Toggle following the file\n",
+  "s      : Toggle statistics\n",
+  "p      : Toggle block list\n",
+  "t      : Toggle section tree\n",
+  "Tab    : Move between panes\n",
+  "Enter  : Open or close tree group\n",
   "?      : Toggle help\n",
 ];
 
 pub const HELP_LINES: u16 = HELP_TEXT.len() as u16;
 
 pub fn draw_help(area: Rect, buf: &mut Buffer) {
-  Clear::default().render(area, buf);
+  Clear.render(area, buf);
   Block::bordered().render(area, buf);
   Paragraph::new("Commands").render(area, buf);
 
@@ -31,8 +37,8 @@ pub fn draw_help(area: Rect, buf: &mut Buffer) {
     Rect {
       x: area.x + 2,
       y: area.y + 1,
-      width: area.width - 2,
-      height: area.height - 1,
+      width: area.width.saturating_sub(3),
+      height: area.height.saturating_sub(2),
     },
     buf,
   );
@@ -1,77 +1,105 @@
-use std::{
-  cmp::min,
-  collections::{HashMap, HashSet},
-};
+use std::{cmp::min, collections::HashSet, ops::Range};
 
 use ratatui::{
   prelude::{Buffer, Rect},
-  style::{Style, Stylize},
+  style::{Color, Style, Stylize},
+  text::{Line, Span},
   widgets::Block,
-  widgets::Widget,
+  widgets::{Paragraph, Widget, Wrap},
 };
 
-use crate::pcapng::{self, PngBlock};
+use hexng::{
+  capture::Capture,
+  pcapng::{to_ascii, to_hex, BlockErrorKind, PngBlock},
+  section::Section,
+};
 
 #[derive(Default)]
 pub struct HexView {
   pos: u32, // Number of lines of the top block that are hidden
   pub cursor: (u16, u16),
   area: Rect,
-  block_areas: Vec<(u32, u16)>,
+  // Id, rows drawn and rows hidden above the top of the view, of each block on screen
+  block_areas: Vec<(u32, u16, u16)>,
   folded: HashSet<u32>,
-  row_counts: HashMap<u32, u16>,
+  // Indexed by block id
+  row_counts: Vec<u16>,
+  // Position of each block's title, plus one past the last block, so the top block can be found
+  // without walking every block
+  starts: Vec<u32>,
   ascii: bool,
+  // Jump to the newest block whenever more are added
+  pub tail: bool,
+  // Block to scroll to on the next draw, once the rows of every block are known
+  jump: Option<u32>,
+  // Block and bytes shown reversed, such as the section selected in the tree
+  pub highlight: Option<(u32, Range<usize>)>,
 }
 
 impl HexView {
-  pub fn draw(&mut self, mut area: Rect, buf: &mut Buffer, data: &Vec<Box<dyn PngBlock>>) {
+  pub fn draw(&mut self, mut area: Rect, buf: &mut Buffer, capture: &mut Capture) {
     if self.area != area {
       // Area changed
       if self.area.width != area.width {
-        self.row_counts.clear()
+        self.row_counts.clear();
+        self.starts.clear();
       }
       self.area = area;
       self.cursor.0 = min(self.cursor.0, area.width - 1);
       self.cursor.1 = min(self.cursor.1, area.height - 1);
-      for block in data {
-        self.row_counts.insert(block.id(), block.rows(area.width));
-      }
+    }
+    let known = self.row_counts.len();
+    if self.starts.is_empty() {
+      self.starts.push(0);
+    }
+    for id in known..capture.block_count() {
+      let Some(rows) = capture.rows(id as u32, area.width) else {
+        break;
+      };
+      self.row_counts.push(rows);
+      let start = self.starts[id] + self.rows(id as u32) as u32 + 1;
+      self.starts.push(start);
+    }
+    if self.tail && self.row_counts.len() > known {
+      self.bottom();
+    }
+    if let Some(id) = self
+      .jump
+      .take()
+      .filter(|&id| (id as usize) < self.row_counts.len())
+    {
+      self.pos = self.get_block_pos(id);
+      self.cursor.1 = 0;
     }
     self.block_areas.clear();
-    let mut current_pos: u32 = 0;
-    for (_, block) in data.iter().enumerate() {
-      let rows;
-      if self.folded.contains(&block.id()) {
-        rows = 1;
-      } else {
-        rows = self.row_counts[&block.id()];
-      }
-      if current_pos + (rows as u32) <= self.pos {
-        if current_pos + (rows as u32) == self.pos {
-          area.y += 1;
-          area.height -= 1;
-          current_pos += rows as u32;
-        } else {
-          current_pos += rows as u32 + 1;
-        }
-        continue;
+    // Skip every block that ends above pos
+    let top = self.starts[1..].partition_point(|&next| next - 1 <= self.pos) as u32;
+    for id in top..self.row_counts.len() as u32 {
+      let start = self.starts[id as usize];
+      if start > self.pos && id == top {
+        // Only the gap above the block is hidden
+        area.y += 1;
+        area.height -= 1;
       }
+      let hidden = self.pos.saturating_sub(start);
+      let Some(block) = capture.block(id) else {
+        break;
+      };
 
-      let mut hidden = 0;
-      if current_pos < self.pos {
-        hidden = self.pos - current_pos;
-      }
-      current_pos += rows as u32 + 1;
-
-      let rows_drawn = pcapng::draw_block(
+      let rows_drawn = draw_block(
         block,
         area,
         buf,
         hidden as u16,
-        self.folded.contains(&block.id()),
+        self.folded.contains(&id),
         self.ascii,
+        self
+          .highlight
+          .as_ref()
+          .filter(|(h, _)| *h == id)
+          .map(|(_, range)| range.clone()),
       );
-      self.block_areas.push((block.id(), rows_drawn));
+      self.block_areas.push((id, rows_drawn, hidden as u16));
       if area.height <= 2 + rows_drawn {
         // Block has filled the remaining area
         break;
@@ -127,45 +155,188 @@ impl HexView {
   }
 
   pub fn bottom(&mut self) {
-    let last_id = self.row_counts.keys().max().unwrap();
-    self.pos = self.get_block_pos(last_id);
+    if let Some(last_id) = self.row_counts.len().checked_sub(1) {
+      self.pos = self.get_block_pos(last_id as u32);
+    }
   }
 
   pub fn top(&mut self) {
     self.pos = 0;
   }
 
-  pub fn id_under_cursor(&self) -> (u32, u16) {
+  // Drops the rows counted for a block and every block after it, once they have been replaced
+  pub fn forget(&mut self, from: u32) {
+    self.row_counts.truncate(from as usize);
+    self.starts.truncate(from as usize + 1);
+  }
+
+  // Returns None when the cursor is below the last block
+  pub fn id_under_cursor(&self) -> Option<(u32, u16)> {
+    self
+      .area_under_cursor()
+      .map(|(id, cursor_y, _)| (id, cursor_y))
+  }
+
+  // Block under the cursor, the cursor's row within what is drawn of it and the rows of it hidden
+  // above the view
+  fn area_under_cursor(&self) -> Option<(u32, u16, u16)> {
     let mut cursor_y = self.cursor.1;
-    for (id, area) in &self.block_areas {
-      if &cursor_y > area {
-        cursor_y -= area + 1;
+    for &(id, rows, hidden) in &self.block_areas {
+      if cursor_y > rows {
+        cursor_y -= rows + 1;
       } else {
-        return (*id, cursor_y);
+        return Some((id, cursor_y, hidden));
       }
     }
-    assert!(false);
-    (0, 0)
+    None
+  }
+
+  // Offset within the block under the cursor of the byte under it, or None on the title row
+  pub fn cursor_offset(&self) -> Option<usize> {
+    let (_, cursor_y, hidden) = self.area_under_cursor()?;
+    if cursor_y == 0 {
+      return None;
+    }
+    let bytes_in_row = ((self.area.width + 1) / 3) as usize;
+    Some((hidden as usize + cursor_y as usize - 1) * bytes_in_row + self.cursor.0 as usize / 3)
   }
 
   pub fn fold(&mut self) {
-    let (id, _) = self.id_under_cursor();
+    let Some((id, _)) = self.id_under_cursor() else {
+      return;
+    };
     if self.folded.contains(&id) {
       self.folded.remove(&id);
     } else {
       self.folded.insert(id);
     }
+    self.restart_after(id);
+  }
+
+  // Scrolls block id to the top on the next draw, with the cursor on its title
+  pub fn show_block(&mut self, id: u32) {
+    self.jump = Some(id);
+  }
+
+  fn get_block_pos(&self, id: u32) -> u32 {
+    self.starts[id as usize]
   }
 
-  fn get_block_pos(&self, id: &u32) -> u32 {
-    let mut pos = 0;
-    for i in 0..*id {
-      pos += self.row_counts[&i] as u32 + 1;
+  fn rows(&self, id: u32) -> u16 {
+    if self.folded.contains(&id) {
+      1
+    } else {
+      self.row_counts[id as usize]
+    }
+  }
+
+  // Moves every block after id to account for a change in its rows
+  fn restart_after(&mut self, id: u32) {
+    for id in id as usize..self.row_counts.len() {
+      self.starts[id + 1] = self.starts[id] + self.rows(id as u32) as u32 + 1;
     }
-    return pos;
   }
 
   pub fn toggle_ascii(&mut self) {
     self.ascii = !self.ascii;
   }
 }
+
+pub fn draw_block(
+  block: &dyn PngBlock,
+  mut area: Rect,
+  buf: &mut Buffer,
+  hidden: u16,
+  folded: bool,
+  ascii: bool,
+  highlight: Option<Range<usize>>,
+) -> u16 {
+  let total_rows = block.rows(area.width);
+  assert!(hidden < total_rows);
+
+  let total_rows_to_print = std::cmp::min(total_rows - hidden, area.height);
+  if total_rows_to_print == 0 {
+    return 0;
+  }
+  let mut rows_to_print = total_rows_to_print;
+
+  if block.error() == &BlockErrorKind::None {
+    Line::raw(block.title_line())
+      .underlined()
+      .bold()
+      .render(area, buf);
+  } else {
+    Line::raw(block.title_line() + " - ERROR " + block.error().description())
+      .underlined()
+      .bold()
+      .fg(Color::Red)
+      .render(area, buf);
+  }
+  if folded || rows_to_print == 1 {
+    return 1;
+  }
+
+  area.y += 1;
+  area.height -= 1;
+  rows_to_print -= 1;
+
+  let bytes_in_row = ((area.width + 1) / 3) as usize;
+  let raw = block.raw();
+  let start: usize = hidden as usize * bytes_in_row;
+  let end: usize = std::cmp::min(
+    (hidden + rows_to_print) as usize * bytes_in_row,
+    std::cmp::min(block.length(), raw.len()),
+  );
+  let mut spans = vec![];
+  let mut fg_colour_index = 0;
+  let mut bg_colour_index = 0;
+  let fg_colours = [
+    Color::White,
+    Color::Red,
+    Color::Green,
+    Color::Magenta,
+    Color::LightBlue,
+  ];
+  let bg_colours = [Color::Black, Color::DarkGray];
+  let sections = block.sections();
+  for section in Section::leaves(&sections) {
+    if section.offset >= end {
+      break;
+    }
+    let print_bytes: fn(&[u8]) -> String = if ascii || section.text {
+      to_ascii
+    } else {
+      to_hex
+    };
+    let section_start = std::cmp::max(section.offset, start);
+    let section_end = std::cmp::min(section.offset + section.length, end);
+    if section_start < section_end {
+      let mut span = Span::raw(print_bytes(&raw[section_start..section_end]));
+      span = if section.invalid {
+        span.fg(Color::White).bg(Color::Red)
+      } else {
+        span
+          .fg(fg_colours[fg_colour_index])
+          .bg(bg_colours[bg_colour_index])
+      };
+      if highlight
+        .as_ref()
+        .is_some_and(|h| h.start <= section.offset && section.offset + section.length <= h.end)
+      {
+        span = span.reversed();
+      }
+      spans.push(span);
+    }
+    fg_colour_index = (fg_colour_index + 1) % fg_colours.len();
+    bg_colour_index = (bg_colour_index + 1) % bg_colours.len();
+  }
+
+  for i in 0..spans.len() {
+    spans.insert(2 * i + 1, Span::raw(" "));
+  }
+
+  Paragraph::new(Line::from(spans))
+    .wrap(Wrap { trim: true })
+    .render(area, buf);
+  total_rows_to_print
+}
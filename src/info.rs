@@ -1,17 +1,28 @@
-use crate::pcapng::PngBlock;
+use hexng::pcapng::{BlockErrorKind, PngBlock};
+use hexng::section::Section;
 
-pub fn get_detail_string(block: &Box<dyn PngBlock>, width: u16, cursor: (u16, u16)) -> String {
-  if cursor.1 == 0 {
-    return block.title_line();
-  }
-  let mut offset = ((cursor.1 - 1) * width + cursor.0) / 3;
+// The section holding the byte at offset along with the value of the group it is part of, or the
+// block's title when there is no offset. The block's error is shown on its title and on the
+// section that contradicts the rest of the block.
+pub fn get_detail_string(block: &dyn PngBlock, offset: Option<usize>) -> String {
+  let error = match block.error() {
+    BlockErrorKind::None => "".to_owned(),
+    error => format!(" - ERROR {}", error.description()),
+  };
+  let Some(offset) = offset else {
+    return block.title_line() + &error;
+  };
 
   let sections = block.sections();
-  for (description, size) in sections {
-    if offset < size as u16 {
-      return description.to_owned();
-    }
-    offset -= size as u16;
+  let Some((section, group)) = Section::find(&sections, offset) else {
+    return "".to_owned();
+  };
+  let mut detail = section.description();
+  if let Some(group) = group {
+    detail += &format!(" ({})", group.description());
+  }
+  if section.invalid {
+    detail += &error;
   }
-  "".to_owned()
+  detail
 }
@@ -1,8 +1,11 @@
 use crate::{
   baseblock::BaseBlock,
   loader::Config,
-  pcapng::{BlockErrorKind, PngBlock},
+  options::{find_option, option_sections},
+  pcapng::{BlockErrorKind, PngBlock, SectionContext},
+  section::{Section, Value},
   types::BlockTypes,
+  util::{read_u16, read_u32},
 };
 
 pub struct InterfaceDescription {
@@ -10,32 +13,58 @@ pub struct InterfaceDescription {
   pub link_type: u16,
   #[allow(dead_code)]
   reserved: u16,
-  snap_length: u32,
+  pub snap_length: u32,
+  pub name: Option<String>,
+  pub timestamp_resolution: u8,
   link_type_str: String,
 }
 
 impl InterfaceDescription {
   pub const SIZE: usize = BaseBlock::SIZE + 8;
 
-  pub fn parse(data: &[u8], id: u32, config: &Config) -> (Self, usize) {
-    let base = BaseBlock::parse(data, id);
-    let link_type = u16::from_le_bytes(data[8..10].try_into().unwrap());
-    let reserved = u16::from_le_bytes(data[10..12].try_into().unwrap());
-    let snap_length = u32::from_le_bytes(data[12..16].try_into().unwrap());
-    (
+  pub fn parse(
+    data: &[u8],
+    id: u32,
+    section: &SectionContext,
+    config: &Config,
+  ) -> Result<(Self, usize), BlockErrorKind> {
+    let little_endian = section.little_endian;
+    let mut base = BaseBlock::parse(data, id, section);
+    if (base.0.length_ as usize) < Self::SIZE {
+      return Err(BlockErrorKind::LengthMismatch);
+    }
+    let link_type = read_u16(&data[8..10], little_endian);
+    let reserved = read_u16(&data[10..12], little_endian);
+    let snap_length = read_u32(&data[12..16], little_endian);
+    let options = base.0.raw_.get(Self::SIZE - 4..base.0.length_ as usize - 4);
+    let name = options
+      .and_then(|options| find_option(options, 2, little_endian))
+      .map(|n| String::from_utf8_lossy(n).trim_end_matches('\0').to_owned());
+    // Microseconds unless if_tsresol says otherwise
+    let timestamp_resolution = options
+      .and_then(|options| find_option(options, 9, little_endian))
+      .filter(|r| r.len() == 1)
+      .map_or(6, |r| r[0]);
+    let link_type_str = match config.link_types.get(&link_type) {
+      Some(l) => l.clone(),
+      None => {
+        base.0.flag(BlockErrorKind::UnknownLinkType);
+        "Unknown".to_owned()
+      }
+    };
+    base.0.check_options(Self::SIZE - 4);
+    Ok((
       InterfaceDescription {
         base: base.0,
         link_type,
         reserved,
         snap_length,
-        link_type_str: config
-          .link_types
-          .get(&link_type)
-          .unwrap_or(&"Unknown".to_owned())
-          .clone(),
+        name,
+        timestamp_resolution,
+        link_type_str,
       },
       base.1,
-    )
+    ))
   }
 }
 
@@ -44,34 +73,41 @@ impl PngBlock for InterfaceDescription {
     self.base.rows(width)
   }
 
-  fn sections(&self) -> Vec<(String, usize)> {
-    let sections: Vec<(String, usize)> = vec![
-      ("Link Type - ".to_owned() + &self.link_type_str, 2),
-      ("Reserved".to_owned(), 2),
-      (
-        "Snap Length - ".to_owned() + &self.snap_length.to_string(),
-        4,
+  fn sections(&self) -> Vec<Section> {
+    let mut sections = vec![
+      Section::new(
+        "Link Type",
+        2,
+        Value::Enum(self.link_type as u64, self.link_type_str.clone()),
       ),
-      ("Options".to_owned(), self.base.length() - Self::SIZE),
+      Section::new("Reserved", 2, Value::None),
+      Section::new("Snap Length", 4, Value::Uint(self.snap_length as u64)),
     ];
-    let mut base_sections = self.base.sections();
-    base_sections.remove(2);
-    base_sections.splice(2..2, sections);
-    assert_eq!(
-      base_sections.iter().map(|s| s.1 as u32).sum::<u32>(),
-      self.base.length_
-    );
-    base_sections
+    sections.extend(option_sections(
+      &self.raw()[Self::SIZE - 4..self.length() - 4],
+      self.block_type(),
+      self.base.little_endian_,
+      None,
+    ));
+    self.base.frame(sections)
   }
 
   fn error(&self) -> &BlockErrorKind {
     self.base.error()
   }
 
+  fn errors(&self) -> &[BlockErrorKind] {
+    self.base.errors()
+  }
+
   fn id(&self) -> u32 {
     self.base.id()
   }
 
+  fn section(&self) -> u32 {
+    self.base.section()
+  }
+
   fn length(&self) -> usize {
     self.base.length()
   }
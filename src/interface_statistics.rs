@@ -0,0 +1,139 @@
+use crate::{
+  baseblock::BaseBlock,
+  loader::Config,
+  options::{find_option, option_sections},
+  packet_data::timestamp_section,
+  pcapng::{BlockErrorKind, Interface, PngBlock, SectionContext},
+  section::{Section, Value},
+  types::BlockTypes,
+  util::{read_u32, read_u64},
+};
+
+pub struct InterfaceStatistics {
+  base: BaseBlock,
+  interface_id: u32,
+  timestamp_upper: u32,
+  timestamp_lower: u32,
+  // For the resolution of the timestamps, when the interface is known
+  interface: Option<Interface>,
+  interface_str: String,
+  dropped: Option<u64>,
+}
+
+impl InterfaceStatistics {
+  pub const SIZE: usize = BaseBlock::SIZE + 12;
+
+  pub fn parse(
+    data: &[u8],
+    id: u32,
+    section: &SectionContext,
+    config: &Config,
+  ) -> Result<(InterfaceStatistics, usize), BlockErrorKind> {
+    let little_endian = section.little_endian;
+    let mut base = BaseBlock::parse(data, id, section);
+    if (base.0.length_ as usize) < Self::SIZE {
+      return Err(BlockErrorKind::LengthMismatch);
+    }
+    let interface_id = read_u32(&data[8..12], little_endian);
+    let timestamp_upper = read_u32(&data[12..16], little_endian);
+    let timestamp_lower = read_u32(&data[16..20], little_endian);
+    let interface = section.interface(interface_id);
+    let interface_str = match &interface {
+      Some(interface) => {
+        let link_type_str = config
+          .link_types
+          .get(&interface.link_type)
+          .map_or("Unknown", |l| l.as_str());
+        match &interface.name {
+          Some(name) => format!("Interface {} ({}, {})", interface_id, name, link_type_str),
+          None => format!("Interface {} ({})", interface_id, link_type_str),
+        }
+      }
+      None => {
+        base.0.flag(BlockErrorKind::UnknownInterface);
+        format!("Interface {} (Unknown)", interface_id)
+      }
+    };
+    let dropped = base
+      .0
+      .raw_
+      .get(Self::SIZE - 4..base.0.length_ as usize - 4)
+      .and_then(|options| find_option(options, 5, little_endian))
+      .filter(|d| d.len() == 8)
+      .map(|d| read_u64(d, little_endian));
+    base.0.check_options(Self::SIZE - 4);
+    Ok((
+      InterfaceStatistics {
+        base: base.0,
+        interface_id,
+        timestamp_upper,
+        timestamp_lower,
+        interface,
+        interface_str,
+        dropped,
+      },
+      base.1,
+    ))
+  }
+}
+
+impl PngBlock for InterfaceStatistics {
+  fn rows(&self, width: u16) -> u16 {
+    self.base.rows(width)
+  }
+
+  fn sections(&self) -> Vec<Section> {
+    let mut sections = vec![
+      Section::new("Interface ID", 4, Value::Uint(self.interface_id as u64)),
+      timestamp_section(
+        "Timestamp",
+        self.timestamp_upper,
+        self.timestamp_lower,
+        self.interface.as_ref(),
+      ),
+    ];
+    sections.extend(option_sections(
+      &self.raw()[Self::SIZE - 4..self.length() - 4],
+      self.block_type(),
+      self.base.little_endian_,
+      self.interface.as_ref(),
+    ));
+    self.base.frame(sections)
+  }
+
+  fn error(&self) -> &BlockErrorKind {
+    self.base.error()
+  }
+
+  fn errors(&self) -> &[BlockErrorKind] {
+    self.base.errors()
+  }
+
+  fn id(&self) -> u32 {
+    self.base.id()
+  }
+
+  fn section(&self) -> u32 {
+    self.base.section()
+  }
+
+  fn length(&self) -> usize {
+    self.base.length()
+  }
+
+  fn block_type(&self) -> &BlockTypes {
+    &BlockTypes::InterfaceStatisticsBlock
+  }
+
+  fn title_line(&self) -> String {
+    let mut title = self.base.title_line() + " - " + &self.interface_str;
+    if let Some(dropped) = self.dropped {
+      title += &format!(" - {} Dropped", dropped);
+    }
+    title
+  }
+
+  fn raw(&self) -> &Vec<u8> {
+    self.base.raw()
+  }
+}
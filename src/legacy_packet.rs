@@ -0,0 +1,85 @@
+use crate::{
+  enhanced_packet::EnhancedPacket,
+  loader::Config,
+  pcapng::{BlockErrorKind, PacketInfo, PngBlock, SectionContext},
+  section::{Section, Value},
+  types::BlockTypes,
+  util::read_u16,
+};
+
+// The obsolete Packet Block, laid out like an Enhanced Packet Block except that the 32-bit
+// Interface ID is split into a 16-bit Interface ID and a Drops Count
+pub struct LegacyPacket {
+  packet: EnhancedPacket,
+}
+
+impl LegacyPacket {
+  pub const SIZE: usize = EnhancedPacket::SIZE;
+
+  pub fn parse(
+    data: &[u8],
+    id: u32,
+    section: &SectionContext,
+    config: &Config,
+  ) -> Result<(LegacyPacket, usize), BlockErrorKind> {
+    let (packet, length) =
+      EnhancedPacket::parse_with(data, id, section, config, |field, little_endian| {
+        let interface_id = read_u16(&field[..2], little_endian);
+        let drops_count = read_u16(&field[2..4], little_endian);
+        (
+          interface_id as u32,
+          vec![
+            Section::new("Interface ID", 2, Value::Uint(interface_id as u64)),
+            Section::new("Drops Count", 2, Value::Uint(drops_count as u64)),
+          ],
+        )
+      })?;
+    Ok((LegacyPacket { packet }, length))
+  }
+}
+
+impl PngBlock for LegacyPacket {
+  fn rows(&self, width: u16) -> u16 {
+    self.packet.rows(width)
+  }
+
+  fn sections(&self) -> Vec<Section> {
+    self.packet.sections()
+  }
+
+  fn error(&self) -> &BlockErrorKind {
+    self.packet.error()
+  }
+
+  fn errors(&self) -> &[BlockErrorKind] {
+    self.packet.errors()
+  }
+
+  fn id(&self) -> u32 {
+    self.packet.id()
+  }
+
+  fn section(&self) -> u32 {
+    self.packet.section()
+  }
+
+  fn length(&self) -> usize {
+    self.packet.length()
+  }
+
+  fn block_type(&self) -> &BlockTypes {
+    self.packet.block_type()
+  }
+
+  fn title_line(&self) -> String {
+    self.packet.title_line()
+  }
+
+  fn raw(&self) -> &Vec<u8> {
+    self.packet.raw()
+  }
+
+  fn packet(&self) -> Option<PacketInfo> {
+    self.packet.packet()
+  }
+}
@@ -0,0 +1,24 @@
+// Parsing for pcapng and classic pcap captures, shared by the hexng binary and other tools. The
+// terminal interface lives in the binary, which is only built with the tui feature.
+pub mod baseblock;
+pub mod capture;
+pub(crate) mod compression;
+pub mod decryption_secrets;
+pub mod enhanced_packet;
+pub mod gap;
+pub mod interface_description;
+pub mod interface_statistics;
+pub mod legacy_packet;
+pub mod loader;
+pub mod name_resolution;
+pub(crate) mod options;
+pub(crate) mod packet_data;
+pub(crate) mod pcap;
+pub mod pcap_header;
+pub mod pcap_record;
+pub mod pcapng;
+pub mod section;
+pub mod section_header;
+pub mod simple_packet;
+pub mod types;
+pub(crate) mod util;
@@ -1,7 +1,12 @@
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::env;
 use std::fs::File;
-use std::io::prelude::*;
+use std::io::{self, prelude::*};
+use std::path::Path;
+
+// Copy of data.json built into the crate, for when there is none next to the executable
+const BUILTIN_CONFIG: &str = include_str!("../data/data.json");
 
 #[derive(Deserialize)]
 pub struct EnhancedPacketConfig {
@@ -21,12 +26,37 @@ pub struct JConfig {
   pub link_types: Option<Vec<(u16, String)>>,
 }
 
-pub fn load(path: &str) -> std::io::Result<Config> {
+// Loads data.json from next to the executable, or the built in copy if there is none, extended by
+// any .hexng.json in the current directory
+pub fn load_config() -> io::Result<Config> {
+  let exe = env::current_exe()?;
+  let mut config = match exe.parent().map(|dir| load(dir.join("data.json"))) {
+    Some(Ok(config)) => config,
+    Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+    _ => builtin_config()?,
+  };
+  let local_config = load(env::current_dir()?.join(".hexng.json"));
+  if let Ok(mut c) = local_config {
+    config.enhanced_packets.append(&mut c.enhanced_packets);
+    config.link_types.extend(c.link_types);
+  }
+  Ok(config)
+}
+
+pub fn load(path: impl AsRef<Path>) -> io::Result<Config> {
   let mut file = File::open(path)?;
   let mut file_contents = String::new();
   file.read_to_string(&mut file_contents)?;
+  parse(&file_contents)
+}
+
+// The data.json the crate was built with
+pub fn builtin_config() -> io::Result<Config> {
+  parse(BUILTIN_CONFIG)
+}
 
-  let v: JConfig = serde_json::from_str(&file_contents)?;
+pub fn parse(json: &str) -> io::Result<Config> {
+  let v: JConfig = serde_json::from_str(json)?;
 
   let enhanced_packets: Vec<EnhancedPacketConfig> = v.enhanced_packets.unwrap_or(vec![]);
   let additional_link_types: HashMap<u16, String> =
@@ -1,30 +1,134 @@
-use clap::Parser;
-use std::io;
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand, ValueEnum};
+use std::{
+  io::{self, BufWriter, IsTerminal, Write},
+  path::PathBuf,
+  process,
+};
+
+use hexng::{capture, loader};
 
 mod app;
-pub mod hexview;
-pub mod pcapng;
-pub mod util;
-pub mod help;
-pub mod baseblock;
-pub mod enhanced_packet;
-pub mod interface_description;
-pub mod section_header;
-pub mod types;
-pub mod info;
-pub mod loader;
+mod check;
+mod dump;
+mod help;
+mod hexview;
+mod info;
+mod packet_list;
+mod stats;
+mod tree;
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
-  path: std::path::PathBuf,
+  #[command(subcommand)]
+  command: Option<Command>,
+  /// Capture file, or - to read from stdin
+  path: Option<PathBuf>,
+  /// Stop at the first corrupt block instead of scanning for the next valid one
+  #[arg(long, global = true)]
+  no_resync: bool,
+  /// Keep watching the file and show blocks as they are written
+  #[arg(long)]
+  follow: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// Print every block and its sections
+  Dump {
+    /// Capture file, or - to read from stdin
+    path: Option<PathBuf>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+  },
+  /// Report structural problems in every block, exiting with status 1 if there are any
+  Check {
+    /// Capture file, or - to read from stdin
+    path: Option<PathBuf>,
+  },
+  /// Summarize the blocks, interfaces, sizes and timestamps in a capture
+  Stats {
+    /// Capture file, or - to read from stdin
+    path: Option<PathBuf>,
+  },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+  Text,
+  /// One object per line for each block
+  Json,
+}
+
+// Reads from stdin when it is piped in and no file is given
+fn input_path(path: Option<PathBuf>) -> Option<PathBuf> {
+  match path {
+    Some(path) if path.as_os_str() == "-" => None,
+    Some(path) => Some(path),
+    None if !io::stdin().is_terminal() => None,
+    None => Cli::command()
+      .error(
+        ErrorKind::MissingRequiredArgument,
+        "a capture file is required",
+      )
+      .exit(),
+  }
 }
 
 fn main() -> io::Result<()> {
   let args = Cli::parse();
-  let _ = args;
-  let mut terminal = ratatui::init();
-  let mut app = app::App::new(args.path)?;
-  let app_result = app.run(&mut terminal);
-  ratatui::restore();
-  app_result
+  let resync = !args.no_resync;
+  let result = match args.command {
+    Some(Command::Dump { path, format }) => {
+      let path = input_path(path);
+      let config = loader::load_config()?;
+      let mut capture = capture::Capture::load(path.as_deref(), config, resync, false)?;
+      capture.wait();
+      let mut out = BufWriter::new(io::stdout().lock());
+      match format {
+        Format::Text => dump::dump_text(&mut capture, &mut out),
+        Format::Json => dump::dump_json(&mut capture, &mut out),
+      }
+    }
+    Some(Command::Check { path }) => {
+      let path = input_path(path);
+      let config = loader::load_config()?;
+      let mut capture = capture::Capture::load(path.as_deref(), config, resync, false)?;
+      capture.wait();
+      let mut out = BufWriter::new(io::stdout().lock());
+      match check::check(&mut capture, &mut out) {
+        Ok(0) => Ok(()),
+        Ok(_) => process::exit(1),
+        Err(e) => Err(e),
+      }
+    }
+    Some(Command::Stats { path }) => {
+      let path = input_path(path);
+      let config = loader::load_config()?;
+      let mut capture = capture::Capture::load(path.as_deref(), config, resync, false)?;
+      capture.wait();
+      let mut stats = stats::Stats::default();
+      stats.update(&capture, usize::MAX);
+      let mut out = BufWriter::new(io::stdout().lock());
+      stats
+        .lines()
+        .iter()
+        .try_for_each(|line| writeln!(out, "{}", line))
+        .and_then(|_| out.flush())
+    }
+    None => {
+      let path = input_path(args.path);
+      let mut terminal = ratatui::init();
+      let mut app = app::App::new(path, resync, args.follow)?;
+      let app_result = app.run(&mut terminal);
+      ratatui::restore();
+      app_result
+    }
+  };
+  match result {
+    // Output piped into something like head that stopped reading
+    Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+    result => result,
+  }
 }
@@ -0,0 +1,271 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::{
+  baseblock::BaseBlock,
+  options::option_sections,
+  pcapng::{BlockErrorKind, PngBlock, SectionContext},
+  section::{Address, Section, Value},
+  types::BlockTypes,
+  util::read_u16,
+};
+
+pub struct NameResolution {
+  base: BaseBlock,
+  sections_: Vec<Section>,
+  // Raw address bytes and the names they resolve to
+  pub names: Vec<(Vec<u8>, String)>,
+}
+
+fn record_name(record_type: u16) -> &'static str {
+  match record_type {
+    0 => "nrb_record_end",
+    1 => "nrb_record_ipv4",
+    2 => "nrb_record_ipv6",
+    3 => "nrb_record_eui48",
+    4 => "nrb_record_eui64",
+    _ => "Unknown Record",
+  }
+}
+
+fn record_address(address: &[u8]) -> Address {
+  match address.len() {
+    4 => Address::Ip(
+      Ipv4Addr::from(<[u8; 4]>::try_from(address).unwrap()).into(),
+      None,
+    ),
+    16 => Address::Ip(
+      Ipv6Addr::from(<[u8; 16]>::try_from(address).unwrap()).into(),
+      None,
+    ),
+    _ => Address::Hardware(address.to_vec()),
+  }
+}
+
+impl NameResolution {
+  pub fn parse(
+    data: &[u8],
+    id: u32,
+    section: &SectionContext,
+  ) -> Result<(NameResolution, usize), BlockErrorKind> {
+    let base = BaseBlock::parse(data, id, section);
+    let mut p = NameResolution {
+      base: base.0,
+      sections_: vec![],
+      names: vec![],
+    };
+    p.parse_records();
+    if p.sections_.iter().map(|s| s.length).sum::<usize>() != p.length() {
+      return Err(BlockErrorKind::LengthMismatch);
+    }
+    Ok((p, base.1))
+  }
+
+  fn parse_records(&mut self) {
+    let little_endian = self.base.little_endian_;
+    let end = self.length() - 4;
+    let raw = &self.base.raw_;
+    let mut records: Vec<Section> = vec![];
+    let mut pos = 8;
+    while pos < end {
+      if end - pos < 4 {
+        records.push(Section::new("Records", end - pos, Value::None).as_invalid());
+        pos = end;
+        break;
+      }
+      let record_type = read_u16(&raw[pos..], little_endian);
+      let length = read_u16(&raw[pos + 2..], little_endian) as usize;
+      let name = record_name(record_type);
+      let mut fields = vec![
+        Section::new(format!("{} Type", name), 2, Value::Uint(record_type as u64)),
+        Section::new(format!("{} Length", name), 2, Value::Uint(length as u64)),
+      ];
+      pos += 4;
+      if record_type == 0 {
+        records.push(Section::group(name, Value::None, fields));
+        break;
+      }
+      if length > end - pos {
+        fields.push(Section::new(name, end - pos, Value::None).as_invalid());
+        records.push(Section::group(name, Value::None, fields));
+        pos = end;
+        break;
+      }
+      let address_length = match record_type {
+        1 => 4,
+        2 => 16,
+        3 => 6,
+        4 => 8,
+        _ => 0,
+      };
+      let mut value = Value::None;
+      if address_length == 0 || address_length > length {
+        if length > 0 {
+          fields.push(Section::new(format!("{} Value", name), length, Value::None));
+        }
+      } else {
+        let address = &raw[pos..pos + address_length];
+        value = Value::Address(record_address(address));
+        fields.push(Section::new(
+          format!("{} Address", name),
+          address_length,
+          value.clone(),
+        ));
+        // Each address is followed by one or more zero terminated names
+        let mut name_start = pos + address_length;
+        while name_start < pos + length {
+          let name_end = raw[name_start..pos + length]
+            .iter()
+            .position(|&b| b == 0)
+            .map_or(pos + length, |n| name_start + n + 1);
+          let resolved = String::from_utf8_lossy(&raw[name_start..name_end])
+            .trim_end_matches('\0')
+            .to_owned();
+          fields.push(Section::new(
+            format!("{} Name", name),
+            name_end - name_start,
+            Value::String(resolved.clone()),
+          ));
+          self.names.push((address.to_vec(), resolved));
+          name_start = name_end;
+        }
+      }
+      pos += length;
+      let padding = std::cmp::min((4 - length % 4) % 4, end - pos);
+      if padding > 0 {
+        fields.push(Section::new(
+          format!("{} Padding", name),
+          padding,
+          Value::None,
+        ));
+        pos += padding;
+      }
+      records.push(Section::group(name, value, fields));
+    }
+    let mut sections = vec![];
+    if !records.is_empty() {
+      sections.push(Section::group("Records", Value::None, records));
+    }
+    sections.extend(option_sections(
+      &raw[pos..end],
+      &BlockTypes::NameResolutionBlock,
+      little_endian,
+      None,
+    ));
+    self.base.check_options(pos);
+    self.sections_ = self.base.frame(sections);
+  }
+}
+
+impl PngBlock for NameResolution {
+  fn rows(&self, width: u16) -> u16 {
+    self.base.rows(width)
+  }
+
+  fn sections(&self) -> Vec<Section> {
+    self.sections_.clone()
+  }
+
+  fn error(&self) -> &BlockErrorKind {
+    self.base.error()
+  }
+
+  fn errors(&self) -> &[BlockErrorKind] {
+    self.base.errors()
+  }
+
+  fn id(&self) -> u32 {
+    self.base.id()
+  }
+
+  fn section(&self) -> u32 {
+    self.base.section()
+  }
+
+  fn length(&self) -> usize {
+    self.base.length()
+  }
+
+  fn block_type(&self) -> &BlockTypes {
+    &BlockTypes::NameResolutionBlock
+  }
+
+  fn title_line(&self) -> String {
+    format!("{} - {} Names", self.base.title_line(), self.names.len())
+  }
+
+  fn raw(&self) -> &Vec<u8> {
+    self.base.raw()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Name Resolution Block around records, each given as its type and value
+  fn block(records: &[(u16, &[u8])]) -> Vec<u8> {
+    let mut body = vec![];
+    for (record_type, value) in records {
+      body.extend(record_type.to_le_bytes());
+      body.extend((value.len() as u16).to_le_bytes());
+      body.extend(*value);
+      body.resize(body.len().next_multiple_of(4), 0);
+    }
+    let length = (body.len() as u32 + 12).to_le_bytes();
+    [&4u32.to_le_bytes()[..], &length, &body, &length].concat()
+  }
+
+  fn names(sections: &[Section]) -> Vec<&str> {
+    sections.iter().map(|s| s.name.as_str()).collect()
+  }
+
+  #[test]
+  fn records() {
+    let data = block(&[
+      (1, b"\xc0\x00\x02\x01a.example\0b\0"),
+      (3, b"\x00\x01\x02\xaa\xbb\xccmac\0"),
+      (0, b""),
+    ]);
+    let (block, length) = NameResolution::parse(&data, 0, &SectionContext::new(0, true)).unwrap();
+    assert_eq!(length, data.len());
+    assert_eq!(*block.error(), BlockErrorKind::None);
+    assert_eq!(
+      block.names,
+      [
+        (vec![192, 0, 2, 1], "a.example".to_owned()),
+        (vec![192, 0, 2, 1], "b".to_owned()),
+        (vec![0, 1, 2, 0xaa, 0xbb, 0xcc], "mac".to_owned()),
+      ]
+    );
+    let sections = block.sections();
+    let records = &sections[2];
+    assert_eq!(
+      names(&records.children),
+      ["nrb_record_ipv4", "nrb_record_eui48", "nrb_record_end"]
+    );
+    assert_eq!(records.children[0].value.to_string(), "192.0.2.1");
+    assert_eq!(
+      names(&records.children[1].children),
+      [
+        "nrb_record_eui48 Type",
+        "nrb_record_eui48 Length",
+        "nrb_record_eui48 Address",
+        "nrb_record_eui48 Name",
+        "nrb_record_eui48 Padding",
+      ]
+    );
+  }
+
+  #[test]
+  fn record_longer_than_block() {
+    let mut data = block(&[(2, &[0; 16])]);
+    // Claim more bytes than the block holds
+    data[10] = 40;
+    let (block, _) = NameResolution::parse(&data, 0, &SectionContext::new(0, true)).unwrap();
+    assert!(block.names.is_empty());
+    let record = &block.sections()[2].children[0];
+    assert_eq!(record.length, 20);
+    assert_eq!(record.children[2].name, "nrb_record_ipv6");
+    assert!(record.children[2].invalid);
+  }
+}
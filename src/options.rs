@@ -0,0 +1,400 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::packet_data::timestamp_section;
+use crate::pcapng::Interface;
+use crate::section::{Address, Mask, Section, Value};
+use crate::types::BlockTypes;
+use crate::util::{read_u16, read_u32, read_u64};
+
+enum OptionKind {
+  String,
+  Bytes,
+  U8,
+  U32,
+  U64,
+  I64,
+  Hex32,
+  Timestamp,
+  TsResol,
+  Ipv4,
+  Ipv6,
+  Mac,
+  Eui,
+  // A Private Enterprise Number followed by a string or bytes
+  CustomString,
+  CustomBytes,
+}
+
+fn option_info(block_type: &BlockTypes, code: u16) -> (&'static str, OptionKind) {
+  match code {
+    0 => return ("opt_endofopt", OptionKind::Bytes),
+    1 => return ("opt_comment", OptionKind::String),
+    2988 | 19372 => return ("opt_custom", OptionKind::CustomString),
+    2989 | 19373 => return ("opt_custom", OptionKind::CustomBytes),
+    _ => (),
+  }
+  match (block_type, code) {
+    (BlockTypes::SectionHeaderBlock, 2) => ("shb_hardware", OptionKind::String),
+    (BlockTypes::SectionHeaderBlock, 3) => ("shb_os", OptionKind::String),
+    (BlockTypes::SectionHeaderBlock, 4) => ("shb_userappl", OptionKind::String),
+    (BlockTypes::InterfaceDescriptionBlock, 2) => ("if_name", OptionKind::String),
+    (BlockTypes::InterfaceDescriptionBlock, 3) => ("if_description", OptionKind::String),
+    (BlockTypes::InterfaceDescriptionBlock, 4) => ("if_IPv4addr", OptionKind::Ipv4),
+    (BlockTypes::InterfaceDescriptionBlock, 5) => ("if_IPv6addr", OptionKind::Ipv6),
+    (BlockTypes::InterfaceDescriptionBlock, 6) => ("if_MACaddr", OptionKind::Mac),
+    (BlockTypes::InterfaceDescriptionBlock, 7) => ("if_EUIaddr", OptionKind::Eui),
+    (BlockTypes::InterfaceDescriptionBlock, 8) => ("if_speed", OptionKind::U64),
+    (BlockTypes::InterfaceDescriptionBlock, 9) => ("if_tsresol", OptionKind::TsResol),
+    (BlockTypes::InterfaceDescriptionBlock, 10) => ("if_tzone", OptionKind::U32),
+    (BlockTypes::InterfaceDescriptionBlock, 11) => ("if_filter", OptionKind::Bytes),
+    (BlockTypes::InterfaceDescriptionBlock, 12) => ("if_os", OptionKind::String),
+    (BlockTypes::InterfaceDescriptionBlock, 13) => ("if_fcslen", OptionKind::U8),
+    (BlockTypes::InterfaceDescriptionBlock, 14) => ("if_tsoffset", OptionKind::I64),
+    (BlockTypes::InterfaceDescriptionBlock, 15) => ("if_hardware", OptionKind::String),
+    (BlockTypes::InterfaceDescriptionBlock, 16) => ("if_txspeed", OptionKind::U64),
+    (BlockTypes::InterfaceDescriptionBlock, 17) => ("if_rxspeed", OptionKind::U64),
+    (BlockTypes::InterfaceDescriptionBlock, 18) => ("if_iana_tzname", OptionKind::String),
+    (BlockTypes::EnhancedPacketBlock, 2) => ("epb_flags", OptionKind::Hex32),
+    (BlockTypes::EnhancedPacketBlock, 3) => ("epb_hash", OptionKind::Bytes),
+    (BlockTypes::EnhancedPacketBlock, 4) => ("epb_dropcount", OptionKind::U64),
+    (BlockTypes::EnhancedPacketBlock, 5) => ("epb_packetid", OptionKind::U64),
+    (BlockTypes::EnhancedPacketBlock, 6) => ("epb_queue", OptionKind::U32),
+    (BlockTypes::EnhancedPacketBlock, 7) => ("epb_verdict", OptionKind::Bytes),
+    (BlockTypes::PacketBlock, 2) => ("pack_flags", OptionKind::Hex32),
+    (BlockTypes::PacketBlock, 3) => ("pack_hash", OptionKind::Bytes),
+    (BlockTypes::NameResolutionBlock, 2) => ("ns_dnsname", OptionKind::String),
+    (BlockTypes::NameResolutionBlock, 3) => ("ns_dnsIP4addr", OptionKind::Ipv4),
+    (BlockTypes::NameResolutionBlock, 4) => ("ns_dnsIP6addr", OptionKind::Ipv6),
+    (BlockTypes::InterfaceStatisticsBlock, 2) => ("isb_starttime", OptionKind::Timestamp),
+    (BlockTypes::InterfaceStatisticsBlock, 3) => ("isb_endtime", OptionKind::Timestamp),
+    (BlockTypes::InterfaceStatisticsBlock, 4) => ("isb_ifrecv", OptionKind::U64),
+    (BlockTypes::InterfaceStatisticsBlock, 5) => ("isb_ifdrop", OptionKind::U64),
+    (BlockTypes::InterfaceStatisticsBlock, 6) => ("isb_filteraccept", OptionKind::U64),
+    (BlockTypes::InterfaceStatisticsBlock, 7) => ("isb_osdrop", OptionKind::U64),
+    (BlockTypes::InterfaceStatisticsBlock, 8) => ("isb_usrdeliv", OptionKind::U64),
+    _ => ("Unknown Option", OptionKind::Bytes),
+  }
+}
+
+fn decode(kind: &OptionKind, value: &[u8], little_endian: bool) -> Value {
+  match kind {
+    OptionKind::String => Value::String(
+      String::from_utf8_lossy(value)
+        .trim_end_matches('\0')
+        .to_owned(),
+    ),
+    OptionKind::Bytes => Value::Bytes(value.to_vec()),
+    OptionKind::U8 if value.len() == 1 => Value::Uint(value[0] as u64),
+    OptionKind::U32 if value.len() == 4 => Value::Uint(read_u32(value, little_endian) as u64),
+    OptionKind::U64 if value.len() == 8 => Value::Uint(read_u64(value, little_endian)),
+    OptionKind::I64 if value.len() == 8 => Value::Int(read_u64(value, little_endian) as i64),
+    OptionKind::Hex32 if value.len() == 4 => Value::Hex(read_u32(value, little_endian) as u64),
+    OptionKind::TsResol if value.len() == 1 => {
+      // The most significant bit selects a power of 2 rather than a power of 10
+      let base = if value[0] & 0x80 == 0 { 10 } else { 2 };
+      let label = format!("{} (units of {}^-{} s)", value[0], base, value[0] & 0x7f);
+      Value::Enum(value[0] as u64, label)
+    }
+    OptionKind::Ipv4 if value.len() == 4 || value.len() == 8 => {
+      let address = Ipv4Addr::from(<[u8; 4]>::try_from(&value[..4]).unwrap());
+      let mask = value
+        .get(4..)
+        .and_then(|mask| <[u8; 4]>::try_from(mask).ok());
+      let mask = mask.map(|mask| Mask::Netmask(Ipv4Addr::from(mask)));
+      Value::Address(Address::Ip(address.into(), mask))
+    }
+    OptionKind::Ipv6 if value.len() == 16 || value.len() == 17 => {
+      let address = Ipv6Addr::from(<[u8; 16]>::try_from(&value[..16]).unwrap());
+      let prefix_length = value.get(16).map(|&length| Mask::PrefixLength(length));
+      Value::Address(Address::Ip(address.into(), prefix_length))
+    }
+    OptionKind::Mac | OptionKind::Eui if value.len() == 6 || value.len() == 8 => {
+      Value::Address(Address::Hardware(value.to_vec()))
+    }
+    // Value has the wrong length for its type
+    _ => Value::Bytes(value.to_vec()),
+  }
+}
+
+// Groups a custom option's Private Enterprise Number with the data of kind that follows it, which
+// gives the group its value
+fn custom_section(name: &str, kind: OptionKind, value: &[u8], little_endian: bool) -> Section {
+  let pen = read_u32(value, little_endian);
+  let mut fields = vec![Section::new(
+    format!("{} PEN", name),
+    4,
+    Value::Uint(pen as u64),
+  )];
+  let mut data = Value::None;
+  if value.len() > 4 {
+    data = decode(&kind, &value[4..], little_endian);
+    fields.push(Section::new(
+      format!("{} Data", name),
+      value.len() - 4,
+      data.clone(),
+    ));
+  }
+  Section::group(name, data, fields)
+}
+
+// Splits an options area into a section for each option, made up of its code, length, value and
+// padding, all grouped under one Options section. Timestamp options are decoded with the resolution
+// of interface when it is known.
+pub fn option_sections(
+  data: &[u8],
+  block_type: &BlockTypes,
+  little_endian: bool,
+  interface: Option<&Interface>,
+) -> Vec<Section> {
+  let mut options: Vec<Section> = vec![];
+  let mut pos = 0;
+  while pos < data.len() {
+    if data.len() - pos < 4 {
+      options.push(Section::new("Options", data.len() - pos, Value::None).as_invalid());
+      break;
+    }
+    let code = read_u16(&data[pos..], little_endian);
+    let length = read_u16(&data[pos + 2..], little_endian) as usize;
+    let (name, kind) = option_info(block_type, code);
+    let mut fields = vec![
+      Section::new(format!("{} Code", name), 2, Value::Uint(code as u64)),
+      Section::new(format!("{} Length", name), 2, Value::Uint(length as u64)),
+    ];
+    pos += 4;
+    if code == 0 {
+      options.push(Section::group(name, Value::None, fields));
+      if pos < data.len() {
+        options.push(Section::new(
+          "Data after opt_endofopt",
+          data.len() - pos,
+          Value::None,
+        ));
+      }
+      break;
+    }
+    if length > data.len() - pos {
+      fields.push(Section::new(name, data.len() - pos, Value::None).as_invalid());
+      options.push(Section::group(name, Value::None, fields));
+      break;
+    }
+    let mut value = Value::None;
+    if length > 0 {
+      let field = match kind {
+        OptionKind::Timestamp if length == 8 => timestamp_section(
+          name,
+          read_u32(&data[pos..], little_endian),
+          read_u32(&data[pos + 4..], little_endian),
+          interface,
+        ),
+        OptionKind::CustomString if length >= 4 => custom_section(
+          name,
+          OptionKind::String,
+          &data[pos..pos + length],
+          little_endian,
+        ),
+        OptionKind::CustomBytes if length >= 4 => custom_section(
+          name,
+          OptionKind::Bytes,
+          &data[pos..pos + length],
+          little_endian,
+        ),
+        _ => Section::new(
+          name,
+          length,
+          decode(&kind, &data[pos..pos + length], little_endian),
+        ),
+      };
+      value = field.value.clone();
+      fields.push(field);
+      pos += length;
+    }
+    let padding = std::cmp::min((4 - length % 4) % 4, data.len() - pos);
+    if padding > 0 {
+      fields.push(Section::new(
+        format!("{} Padding", name),
+        padding,
+        Value::None,
+      ));
+      pos += padding;
+    }
+    options.push(Section::group(name, value, fields));
+  }
+  if options.is_empty() {
+    return vec![];
+  }
+  vec![Section::group("Options", Value::None, options)]
+}
+
+// Whether an options area is closed by opt_endofopt
+pub fn has_end_of_options(data: &[u8], little_endian: bool) -> bool {
+  let mut pos = 0;
+  while data.len() - pos >= 4 {
+    let code = read_u16(&data[pos..], little_endian);
+    let length = read_u16(&data[pos + 2..], little_endian) as usize;
+    if code == 0 {
+      return true;
+    }
+    pos += 4 + length + (4 - length % 4) % 4;
+    pos = std::cmp::min(pos, data.len());
+  }
+  false
+}
+
+// Returns the value of the first option with the given code
+pub fn find_option(data: &[u8], code: u16, little_endian: bool) -> Option<&[u8]> {
+  let mut pos = 0;
+  while data.len() - pos >= 4 {
+    let option_code = read_u16(&data[pos..], little_endian);
+    let length = read_u16(&data[pos + 2..], little_endian) as usize;
+    pos += 4;
+    if option_code == 0 || length > data.len() - pos {
+      return None;
+    }
+    if option_code == code {
+      return Some(&data[pos..pos + length]);
+    }
+    pos += length + (4 - length % 4) % 4;
+    pos = std::cmp::min(pos, data.len());
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::*;
+
+  fn option(code: u16, value: &[u8]) -> Vec<u8> {
+    let mut data = [code.to_le_bytes(), (value.len() as u16).to_le_bytes()].concat();
+    data.extend(value);
+    data.resize(data.len().next_multiple_of(4), 0);
+    data
+  }
+
+  fn names(sections: &[Section]) -> Vec<&str> {
+    sections.iter().map(|s| s.name.as_str()).collect()
+  }
+
+  #[test]
+  fn options_and_padding() {
+    let data = [option(2, b"eth0"), option(9, &[9]), option(0, &[])].concat();
+    let options = option_sections(&data, &BlockTypes::InterfaceDescriptionBlock, true, None);
+    assert_eq!(names(&options), ["Options"]);
+    assert_eq!(options[0].length, data.len());
+    let options = &options[0].children;
+    assert_eq!(names(options), ["if_name", "if_tsresol", "opt_endofopt"]);
+    assert_eq!(options[0].value, Value::String("eth0".to_owned()));
+    assert_eq!(
+      names(&options[1].children),
+      [
+        "if_tsresol Code",
+        "if_tsresol Length",
+        "if_tsresol",
+        "if_tsresol Padding"
+      ]
+    );
+    assert_eq!(options[1].length, 8);
+    assert!(has_end_of_options(&data, true));
+  }
+
+  #[test]
+  fn truncated_option() {
+    let data = option(1, b"comment");
+    let options = option_sections(&data[..8], &BlockTypes::EnhancedPacketBlock, true, None);
+    let comment = &options[0].children[0];
+    assert_eq!(comment.length, 8);
+    assert_eq!(comment.children[2].value, Value::None);
+    assert!(comment.children[2].invalid);
+    assert!(!has_end_of_options(&data, true));
+  }
+
+  #[test]
+  fn big_endian() {
+    let data = [0, 8, 0, 8, 0, 0, 0, 0, 0, 0, 0x03, 0xe8];
+    let options = option_sections(&data, &BlockTypes::InterfaceDescriptionBlock, false, None);
+    assert_eq!(options[0].children[0].value, Value::Uint(1000));
+  }
+
+  #[test]
+  fn timestamp_option() {
+    let data = option(2, &[0, 0, 0, 0, 0x40, 0x42, 0x0f, 0]);
+    let interface = Interface {
+      link_type: 1,
+      snap_length: 0,
+      name: None,
+      timestamp_resolution: 6,
+    };
+    let block_type = BlockTypes::InterfaceStatisticsBlock;
+    let options = option_sections(&data, &block_type, true, Some(&interface));
+    let start = &options[0].children[0];
+    assert_eq!(start.value, Value::Timestamp(Duration::from_secs(1)));
+    assert_eq!(
+      names(&start.children[2].children),
+      ["isb_starttime Upper", "isb_starttime Lower"]
+    );
+    let options = option_sections(&data, &block_type, true, None);
+    assert_eq!(options[0].children[0].value, Value::Uint(1_000_000));
+  }
+
+  #[test]
+  fn address_options() {
+    let mut ipv6 = vec![0x20, 0x01, 0x0d, 0xb8];
+    ipv6.resize(16, 0);
+    ipv6.push(64);
+    let data = [
+      option(4, &[192, 168, 1, 2, 255, 255, 255, 0]),
+      option(5, &ipv6),
+      option(6, &[0, 1, 2, 0xaa, 0xbb, 0xcc]),
+    ]
+    .concat();
+    let options = option_sections(&data, &BlockTypes::InterfaceDescriptionBlock, true, None);
+    let values: Vec<_> = options[0]
+      .children
+      .iter()
+      .map(|o| o.value.to_string())
+      .collect();
+    assert_eq!(
+      values,
+      [
+        "192.168.1.2/255.255.255.0",
+        "2001:db8::/64",
+        "00:01:02:aa:bb:cc"
+      ]
+    );
+    assert_eq!(
+      options[0].children[0].value,
+      Value::Address(Address::Ip(
+        Ipv4Addr::new(192, 168, 1, 2).into(),
+        Some(Mask::Netmask(Ipv4Addr::new(255, 255, 255, 0)))
+      ))
+    );
+  }
+
+  #[test]
+  fn custom_option() {
+    let data = [
+      option(2988, b"\x00\x7f\x00\x00abc"),
+      option(2989, &[1, 0, 0, 0]),
+    ]
+    .concat();
+    let options = option_sections(&data, &BlockTypes::EnhancedPacketBlock, true, None);
+    let custom = &options[0].children[0];
+    assert_eq!(custom.value, Value::String("abc".to_owned()));
+    let fields = &custom.children[2];
+    assert_eq!(
+      names(&fields.children),
+      ["opt_custom PEN", "opt_custom Data"]
+    );
+    assert_eq!(fields.children[0].value, Value::Uint(32512));
+    // No data after the PEN
+    let custom = &options[0].children[1];
+    assert_eq!(custom.value, Value::None);
+    assert_eq!(custom.children[2].children[0].value, Value::Uint(1));
+  }
+
+  #[test]
+  fn no_options() {
+    assert!(option_sections(&[], &BlockTypes::EnhancedPacketBlock, true, None).is_empty());
+  }
+}
@@ -0,0 +1,103 @@
+use crate::baseblock::BaseBlock;
+use crate::loader::Config;
+use crate::pcapng::{BlockErrorKind, Interface, SectionContext};
+use crate::section::{Section, Value};
+use crate::util::read_uint;
+
+// Looks up the link type of the interface a packet was captured on, flagging the block if the
+// interface or its link type is unknown
+pub fn resolve_link_type(
+  interface_id: u32,
+  section: &SectionContext,
+  config: &Config,
+  base: &mut BaseBlock,
+) -> (Option<u16>, String) {
+  match section.interface(interface_id) {
+    Some(interface) => match config.link_types.get(&interface.link_type) {
+      Some(link_type_str) => (Some(interface.link_type), link_type_str.clone()),
+      None => {
+        base.flag(BlockErrorKind::UnknownLinkType);
+        (Some(interface.link_type), "Unknown".to_owned())
+      }
+    },
+    None => {
+      base.flag(BlockErrorKind::UnknownInterface);
+      (None, "Unknown Interface".to_owned())
+    }
+  }
+}
+
+// Flags a packet claiming to have captured more than was on the wire or than its interface allows
+pub fn check_lengths(
+  interface_id: u32,
+  captured: u32,
+  original: u32,
+  section: &SectionContext,
+  base: &mut BaseBlock,
+) {
+  if captured > original {
+    base.flag(BlockErrorKind::OriginalLengthExceeded);
+  }
+  // A snap length of zero means there is no limit
+  if let Some(interface) = section.interface(interface_id) {
+    if interface.snap_length != 0 && captured > interface.snap_length {
+      base.flag(BlockErrorKind::SnapLengthExceeded);
+    }
+  }
+}
+
+// Groups the two halves of a pcapng timestamp, decoding it with the resolution of its interface
+// when it is known
+pub fn timestamp_section(
+  name: &str,
+  upper: u32,
+  lower: u32,
+  interface: Option<&Interface>,
+) -> Section {
+  let ticks = ((upper as u64) << 32) | lower as u64;
+  let timestamp = match interface {
+    Some(interface) => Value::Timestamp(interface.timestamp(ticks)),
+    None => Value::Uint(ticks),
+  };
+  Section::group(
+    name,
+    timestamp,
+    vec![
+      Section::new(format!("{} Upper", name), 4, Value::Uint(upper as u64)),
+      Section::new(format!("{} Lower", name), 4, Value::Uint(lower as u64)),
+    ],
+  )
+}
+
+// Splits captured packet data into the fields configured for its link type in data.json, followed
+// by whatever data remains. Fields holding an address from a Name Resolution Block show its name.
+pub fn packet_data_section(
+  data: &[u8],
+  link_type: Option<u16>,
+  section: &SectionContext,
+  config: &Config,
+) -> Section {
+  let mut fields: Vec<Section> = vec![];
+  let mut sum = 0;
+  for en in &config.enhanced_packets {
+    if Some(en.linktype) != link_type {
+      continue;
+    }
+    for s in &en.sections {
+      if sum + s.1 > data.len() {
+        break;
+      }
+      let field = &data[sum..sum + s.1];
+      let number = read_uint(field, section.little_endian);
+      let value = match section.name(field) {
+        Some(name) => Value::Enum(number, format!("{} ({})", number, name)),
+        None => Value::Uint(number),
+      };
+      fields.push(Section::new(s.0.clone(), s.1, value));
+      sum += s.1;
+    }
+    break;
+  }
+  fields.push(Section::new("Data", data.len() - sum, Value::None));
+  Section::group("Packet Data", Value::None, fields).as_packet_data()
+}
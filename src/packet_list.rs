@@ -0,0 +1,183 @@
+use ratatui::{
+  prelude::{Buffer, Rect},
+  style::{Color, Style, Stylize},
+  text::Line,
+  widgets::{Block, Widget},
+};
+
+use hexng::{
+  capture::Capture,
+  pcapng::{BlockErrorKind, PngBlock},
+  section::{Section, Value},
+  types::block_type_str,
+};
+
+// One row per block, like the packet list in Wireshark
+#[derive(Default)]
+pub struct PacketList {
+  // First block shown
+  scroll: usize,
+  // Rows shown by the last draw, not counting the header
+  height: usize,
+  // The block under the hex cursor, or the one to move the hex view to when focused
+  pub selected: Option<u32>,
+  // Keys move through the list rather than the hex view
+  pub focused: bool,
+}
+
+impl PacketList {
+  pub fn down(&mut self, count: usize) {
+    self.selected = match self.selected {
+      Some(id) => Some((id + 1).min(count.saturating_sub(1) as u32)),
+      None if count > 0 => Some(0),
+      None => None,
+    };
+  }
+
+  pub fn up(&mut self) {
+    self.selected = self.selected.map(|id| id.saturating_sub(1));
+  }
+
+  pub fn down_half(&mut self, count: usize) {
+    for _ in 0..self.height / 2 {
+      self.down(count);
+    }
+  }
+
+  pub fn up_half(&mut self) {
+    for _ in 0..self.height / 2 {
+      self.up();
+    }
+  }
+
+  pub fn bottom(&mut self, count: usize) {
+    self.selected = count.checked_sub(1).map(|id| id as u32);
+  }
+
+  pub fn top(&mut self, count: usize) {
+    self.selected = (count > 0).then_some(0);
+  }
+
+  pub fn draw(&mut self, area: Rect, buf: &mut Buffer, capture: &Capture) {
+    let mut block = Block::bordered().title("Blocks");
+    if self.focused {
+      block = block.border_style(Style::new().bold());
+    }
+    let inner = block.inner(area);
+    block.render(area, buf);
+    if inner.height == 0 {
+      return;
+    }
+    Line::raw(columns([
+      "Block",
+      "Type",
+      "If",
+      "Timestamp",
+      "Length",
+      "Link Type",
+      "Summary",
+    ]))
+    .bold()
+    .render(inner, buf);
+    self.height = inner.height as usize - 1;
+
+    if let Some(selected) = self.selected.map(|id| id as usize) {
+      // Keep the selection in view
+      if selected < self.scroll {
+        self.scroll = selected;
+      } else if selected >= self.scroll + self.height {
+        self.scroll = selected + 1 - self.height;
+      }
+    }
+    let count = capture.block_count();
+    self.scroll = self.scroll.min(count.saturating_sub(self.height));
+
+    for (row, id) in (self.scroll..count).take(self.height).enumerate() {
+      let Some(block) = capture.decode(id as u32) else {
+        break;
+      };
+      let mut style = Style::new();
+      if *block.error() != BlockErrorKind::None {
+        style = style.fg(Color::Red);
+      }
+      if self.selected == Some(id as u32) {
+        style = if self.focused {
+          style.reversed()
+        } else {
+          style.bold().underlined()
+        };
+      }
+      Line::styled(row_text(capture, block.as_ref()), style).render(
+        Rect {
+          y: inner.y + 1 + row as u16,
+          height: 1,
+          ..inner
+        },
+        buf,
+      );
+    }
+  }
+}
+
+// Pads and cuts each column so that rows line up under the header
+fn columns(values: [&str; 7]) -> String {
+  let [id, block_type, interface, timestamp, length, link_type, summary] = values;
+  format!(
+    "{:<8} {:<27.27} {:<4} {:<20} {:>6}  {:<15.15} {}",
+    id, block_type, interface, timestamp, length, link_type, summary
+  )
+}
+
+fn row_text(capture: &Capture, block: &dyn PngBlock) -> String {
+  let (interface, timestamp, length, link_type) = match block.packet() {
+    Some(packet) => {
+      let interface = capture
+        .context(block.id())
+        .and_then(|c| c.interface(packet.interface_id));
+      let timestamp = match (packet.timestamp, &interface) {
+        (Some(ticks), Some(interface)) => Value::Timestamp(interface.timestamp(ticks)).to_string(),
+        _ => "".to_owned(),
+      };
+      let link_type = match &interface {
+        Some(interface) => match capture.config().link_types.get(&interface.link_type) {
+          Some(link_type) => link_type.clone(),
+          None => format!("Unknown ({})", interface.link_type),
+        },
+        None => "Unknown".to_owned(),
+      };
+      (
+        packet.interface_id.to_string(),
+        timestamp,
+        packet.captured_length.to_string(),
+        link_type,
+      )
+    }
+    None => Default::default(),
+  };
+  columns([
+    &block.id().to_string(),
+    &block_type_str(block.block_type()),
+    &interface,
+    &timestamp,
+    &length,
+    &link_type,
+    &summary(block),
+  ])
+}
+
+// The fields data.json decodes from the start of the packet data, or what is wrong with the block
+fn summary(block: &dyn PngBlock) -> String {
+  if *block.error() != BlockErrorKind::None {
+    return block.error().description().to_owned();
+  }
+  let sections = block.sections();
+  let Some(packet_data) = sections.iter().find(|s| s.packet_data) else {
+    return "".to_owned();
+  };
+  Section::leaves(&packet_data.children)
+    .iter()
+    .filter(|s| s.name != "Data")
+    .map(|s| s.description())
+    .collect::<Vec<_>>()
+    .join(", ")
+}
@@ -0,0 +1,167 @@
+use crate::baseblock::BaseBlock;
+use crate::loader::Config;
+use crate::pcap_header::PcapHeader;
+use crate::pcap_record::PcapRecord;
+use crate::pcapng::{BlockErrorKind, EntryKind, PngBlock, SectionContext};
+use crate::types::BlockTypes;
+use crate::util::read_u32;
+
+const MAGIC_MICROSECONDS: u32 = 0xa1b2c3d4;
+pub(crate) const MAGIC_NANOSECONDS: u32 = 0xa1b23c4d;
+
+// Returns the byte order of a classic libpcap file, or None if the data is not one
+pub fn byte_order(data: &[u8]) -> Option<bool> {
+  if data.len() < 4 {
+    return None;
+  }
+  match read_u32(data, true) {
+    MAGIC_MICROSECONDS | MAGIC_NANOSECONDS => Some(true),
+    m if m.swap_bytes() == MAGIC_MICROSECONDS || m.swap_bytes() == MAGIC_NANOSECONDS => Some(false),
+    _ => None,
+  }
+}
+
+// Returns the length of the record at the start of data, or None if it is cut short
+pub fn record_length(data: &[u8], little_endian: bool) -> Option<usize> {
+  if data.len() < PcapRecord::SIZE {
+    return None;
+  }
+  let length = PcapRecord::SIZE + read_u32(&data[8..12], little_endian) as usize;
+  (length <= data.len()).then_some(length)
+}
+
+// Decodes the global header or a record found by the scanner
+pub fn decode(
+  data: &[u8],
+  kind: EntryKind,
+  id: u32,
+  section: &SectionContext,
+  config: &Config,
+) -> Box<dyn PngBlock> {
+  let (result, block_type): (Result<Box<dyn PngBlock>, BlockErrorKind>, BlockTypes) = match kind {
+    EntryKind::PcapRecord { nanoseconds } => (
+      PcapRecord::parse(data, id, section, nanoseconds, config)
+        .map(|r| Box::new(r.0) as Box<dyn PngBlock>),
+      BlockTypes::PcapRecord,
+    ),
+    _ => (
+      PcapHeader::parse(data, id, section, config).map(|h| Box::new(h.0) as Box<dyn PngBlock>),
+      BlockTypes::PcapFileHeader,
+    ),
+  };
+  match result {
+    Ok(block) => block,
+    Err(error) => Box::new(truncated(data, id, block_type, error, section)),
+  }
+}
+
+// Shows whatever is left of a structure that does not fit in the file
+fn truncated(
+  data: &[u8],
+  id: u32,
+  block_type: BlockTypes,
+  error: BlockErrorKind,
+  section: &SectionContext,
+) -> BaseBlock {
+  BaseBlock::new(
+    data.to_vec(),
+    block_type,
+    data.len() as u32,
+    vec![],
+    id,
+    error,
+    section,
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::*;
+  use crate::{loader::builtin_config, pcapng::parse, section::Value};
+
+  // Classic pcap file with an Ethernet link type and two 4-byte packets, a second apart
+  fn capture(magic: u32, little_endian: bool) -> Vec<u8> {
+    let word = |w: u32| {
+      if little_endian {
+        w.to_le_bytes()
+      } else {
+        w.to_be_bytes()
+      }
+    };
+    let half = |h: u16| {
+      if little_endian {
+        h.to_le_bytes()
+      } else {
+        h.to_be_bytes()
+      }
+    };
+    let mut data = [&word(magic)[..], &half(2), &half(4)].concat();
+    for w in [0, 0, 65535, 1] {
+      data.extend(word(w));
+    }
+    for seconds in [100, 101] {
+      for w in [seconds, 250, 4, 4] {
+        data.extend(word(w));
+      }
+      data.extend([1, 2, 3, 4]);
+    }
+    data
+  }
+
+  fn check(data: &[u8], fraction: Duration) {
+    let blocks = parse(data, &builtin_config().unwrap(), true).unwrap();
+    assert_eq!(blocks.len(), 3);
+    assert!(matches!(blocks[0].block_type(), BlockTypes::PcapFileHeader));
+    assert!(blocks[1..]
+      .iter()
+      .all(|b| matches!(b.block_type(), BlockTypes::PcapRecord)));
+    assert!(blocks.iter().all(|b| *b.error() == BlockErrorKind::None));
+    let timestamp = &blocks[2].sections()[0];
+    assert_eq!(
+      timestamp.value,
+      Value::Timestamp(Duration::from_secs(101) + fraction)
+    );
+    assert_eq!(blocks[2].packet().unwrap().captured_length, 4);
+  }
+
+  #[test]
+  fn byte_orders() {
+    assert_eq!(byte_order(&capture(MAGIC_MICROSECONDS, true)), Some(true));
+    assert_eq!(byte_order(&capture(MAGIC_MICROSECONDS, false)), Some(false));
+    assert_eq!(byte_order(&capture(MAGIC_NANOSECONDS, false)), Some(false));
+    assert_eq!(byte_order(&[0x0a, 0x0d, 0x0d, 0x0a]), None);
+    assert_eq!(byte_order(&[0xd4, 0xc3]), None);
+  }
+
+  #[test]
+  fn microseconds() {
+    check(
+      &capture(MAGIC_MICROSECONDS, true),
+      Duration::from_micros(250),
+    );
+    check(
+      &capture(MAGIC_MICROSECONDS, false),
+      Duration::from_micros(250),
+    );
+  }
+
+  #[test]
+  fn nanoseconds() {
+    check(&capture(MAGIC_NANOSECONDS, true), Duration::from_nanos(250));
+    check(
+      &capture(MAGIC_NANOSECONDS, false),
+      Duration::from_nanos(250),
+    );
+  }
+
+  #[test]
+  fn truncated_record() {
+    let data = capture(MAGIC_MICROSECONDS, true);
+    assert_eq!(record_length(&data[24..], true), Some(20));
+    assert_eq!(record_length(&data[24..40], true), None);
+    let blocks = parse(&data[..data.len() - 2], &builtin_config().unwrap(), true).unwrap();
+    assert_eq!(*blocks[2].error(), BlockErrorKind::Truncated);
+  }
+}
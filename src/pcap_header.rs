@@ -0,0 +1,148 @@
+use crate::{
+  baseblock::BaseBlock,
+  loader::Config,
+  pcap::MAGIC_NANOSECONDS,
+  pcapng::{BlockErrorKind, PngBlock, SectionContext},
+  section::{Section, Value},
+  types::BlockTypes,
+  util::{read_u16, read_u32},
+};
+
+pub struct PcapHeader {
+  base: BaseBlock,
+  magic: u32,
+  major_version: u16,
+  minor_version: u16,
+  reserved1: u32,
+  reserved2: u32,
+  pub snap_length: u32,
+  pub link_type: u16,
+  pub nanoseconds: bool,
+  link_type_str: String,
+}
+
+impl PcapHeader {
+  pub const SIZE: usize = 24;
+
+  pub fn parse(
+    data: &[u8],
+    id: u32,
+    section: &SectionContext,
+    config: &Config,
+  ) -> Result<(PcapHeader, usize), BlockErrorKind> {
+    let little_endian = section.little_endian;
+    if data.len() < Self::SIZE {
+      return Err(BlockErrorKind::Truncated);
+    }
+    let magic = read_u32(&data[..4], little_endian);
+    let link_type = read_u32(&data[20..24], little_endian);
+    // The upper bits of the link type field hold FCS information
+    let link_type = (link_type & 0xffff) as u16;
+    let mut base = BaseBlock::new(
+      data[..Self::SIZE].to_vec(),
+      BlockTypes::PcapFileHeader,
+      Self::SIZE as u32,
+      vec![],
+      id,
+      BlockErrorKind::None,
+      section,
+    );
+    let link_type_str = match config.link_types.get(&link_type) {
+      Some(l) => l.clone(),
+      None => {
+        base.flag(BlockErrorKind::UnknownLinkType);
+        "Unknown".to_owned()
+      }
+    };
+    let major_version = read_u16(&data[4..6], little_endian);
+    let minor_version = read_u16(&data[6..8], little_endian);
+    if major_version != 2 || minor_version != 4 {
+      base.flag(BlockErrorKind::UnknownVersion);
+    }
+    Ok((
+      PcapHeader {
+        base,
+        magic,
+        major_version,
+        minor_version,
+        reserved1: read_u32(&data[8..12], little_endian),
+        reserved2: read_u32(&data[12..16], little_endian),
+        snap_length: read_u32(&data[16..20], little_endian),
+        link_type,
+        nanoseconds: magic == MAGIC_NANOSECONDS,
+        link_type_str,
+      },
+      Self::SIZE,
+    ))
+  }
+}
+
+impl PngBlock for PcapHeader {
+  fn rows(&self, width: u16) -> u16 {
+    self.base.rows(width)
+  }
+
+  fn sections(&self) -> Vec<Section> {
+    let magic = format!(
+      "{:#010x} ({}, {})",
+      self.magic,
+      if self.nanoseconds {
+        "Nanoseconds"
+      } else {
+        "Microseconds"
+      },
+      if self.base.little_endian_ {
+        "Little Endian"
+      } else {
+        "Big Endian"
+      }
+    );
+    let mut sections = vec![
+      Section::new("Magic Number", 4, Value::Enum(self.magic as u64, magic)),
+      Section::new("Major Version", 2, Value::Uint(self.major_version as u64)),
+      Section::new("Minor Version", 2, Value::Uint(self.minor_version as u64)),
+      Section::new("Reserved1", 4, Value::Uint(self.reserved1 as u64)),
+      Section::new("Reserved2", 4, Value::Uint(self.reserved2 as u64)),
+      Section::new("Snap Length", 4, Value::Uint(self.snap_length as u64)),
+      Section::new(
+        "Link Type",
+        4,
+        Value::Enum(self.link_type as u64, self.link_type_str.clone()),
+      ),
+    ];
+    Section::place(&mut sections, 0, self.base.little_endian_);
+    sections
+  }
+
+  fn error(&self) -> &BlockErrorKind {
+    self.base.error()
+  }
+
+  fn errors(&self) -> &[BlockErrorKind] {
+    self.base.errors()
+  }
+
+  fn id(&self) -> u32 {
+    self.base.id()
+  }
+
+  fn section(&self) -> u32 {
+    self.base.section()
+  }
+
+  fn length(&self) -> usize {
+    self.base.length()
+  }
+
+  fn block_type(&self) -> &BlockTypes {
+    &BlockTypes::PcapFileHeader
+  }
+
+  fn title_line(&self) -> String {
+    self.base.title_line() + " - " + &self.link_type_str
+  }
+
+  fn raw(&self) -> &Vec<u8> {
+    self.base.raw()
+  }
+}
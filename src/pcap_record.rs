@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use crate::{
+  baseblock::BaseBlock,
+  loader::Config,
+  packet_data::{check_lengths, packet_data_section, resolve_link_type},
+  pcapng::{BlockErrorKind, PacketInfo, PngBlock, SectionContext},
+  section::{Section, Value},
+  types::BlockTypes,
+  util::read_u32,
+};
+
+pub struct PcapRecord {
+  base: BaseBlock,
+  timestamp_seconds: u32,
+  timestamp_fraction: u32,
+  nanoseconds: bool,
+  captured_packet_length: u32,
+  original_packet_length: u32,
+  link_type: Option<u16>,
+  sections_: Vec<Section>,
+  link_type_str: String,
+}
+
+impl PcapRecord {
+  pub const SIZE: usize = 16;
+
+  pub fn parse(
+    data: &[u8],
+    id: u32,
+    section: &SectionContext,
+    nanoseconds: bool,
+    config: &Config,
+  ) -> Result<(PcapRecord, usize), BlockErrorKind> {
+    let little_endian = section.little_endian;
+    if data.len() < Self::SIZE {
+      return Err(BlockErrorKind::Truncated);
+    }
+    let captured_packet_length = read_u32(&data[8..12], little_endian);
+    let length = Self::SIZE + captured_packet_length as usize;
+    if length > data.len() {
+      return Err(BlockErrorKind::Truncated);
+    }
+    let mut base = BaseBlock::new(
+      data[..length].to_vec(),
+      BlockTypes::PcapRecord,
+      length as u32,
+      vec![],
+      id,
+      BlockErrorKind::None,
+      section,
+    );
+    let (link_type, link_type_str) = resolve_link_type(0, section, config, &mut base);
+    let original_packet_length = read_u32(&data[12..16], little_endian);
+    check_lengths(
+      0,
+      captured_packet_length,
+      original_packet_length,
+      section,
+      &mut base,
+    );
+    let mut p = PcapRecord {
+      base,
+      timestamp_seconds: read_u32(&data[..4], little_endian),
+      timestamp_fraction: read_u32(&data[4..8], little_endian),
+      nanoseconds,
+      captured_packet_length,
+      original_packet_length,
+      link_type,
+      sections_: vec![],
+      link_type_str,
+    };
+    p.sections_ = p.sections_impl(section, config);
+    Ok((p, length))
+  }
+
+  fn sections_impl(&self, section: &SectionContext, config: &Config) -> Vec<Section> {
+    let (fraction_name, nanoseconds) = if self.nanoseconds {
+      ("Timestamp Nanoseconds", self.timestamp_fraction as u64)
+    } else {
+      (
+        "Timestamp Microseconds",
+        self.timestamp_fraction as u64 * 1000,
+      )
+    };
+    let timestamp =
+      Duration::from_secs(self.timestamp_seconds as u64) + Duration::from_nanos(nanoseconds);
+    let mut sections = vec![
+      Section::group(
+        "Timestamp",
+        Value::Timestamp(timestamp),
+        vec![
+          Section::new(
+            "Timestamp Seconds",
+            4,
+            Value::Uint(self.timestamp_seconds as u64),
+          ),
+          Section::new(
+            fraction_name,
+            4,
+            Value::Uint(self.timestamp_fraction as u64),
+          ),
+        ],
+      ),
+      Section::new(
+        "Captured Packet Length",
+        4,
+        Value::Uint(self.captured_packet_length as u64),
+      ),
+      Section::new(
+        "Original Packet Length",
+        4,
+        Value::Uint(self.original_packet_length as u64),
+      ),
+      packet_data_section(&self.raw()[Self::SIZE..], self.link_type, section, config),
+    ];
+    Section::place(&mut sections, 0, self.base.little_endian_);
+    sections
+  }
+}
+
+impl PngBlock for PcapRecord {
+  fn rows(&self, width: u16) -> u16 {
+    self.base.rows(width)
+  }
+
+  fn sections(&self) -> Vec<Section> {
+    self.sections_.clone()
+  }
+
+  fn error(&self) -> &BlockErrorKind {
+    self.base.error()
+  }
+
+  fn errors(&self) -> &[BlockErrorKind] {
+    self.base.errors()
+  }
+
+  fn id(&self) -> u32 {
+    self.base.id()
+  }
+
+  fn section(&self) -> u32 {
+    self.base.section()
+  }
+
+  fn length(&self) -> usize {
+    self.base.length()
+  }
+
+  fn block_type(&self) -> &BlockTypes {
+    &BlockTypes::PcapRecord
+  }
+
+  fn title_line(&self) -> String {
+    self.base.title_line() + " - " + &self.link_type_str
+  }
+
+  fn raw(&self) -> &Vec<u8> {
+    self.base.raw()
+  }
+
+  fn packet(&self) -> Option<PacketInfo> {
+    // Matches the resolution the scanner gives the file's interface
+    let units: u64 = if self.nanoseconds {
+      1_000_000_000
+    } else {
+      1_000_000
+    };
+    Some(PacketInfo {
+      interface_id: 0,
+      timestamp: Some(self.timestamp_seconds as u64 * units + self.timestamp_fraction as u64),
+      captured_length: self.captured_packet_length,
+      original_length: self.original_packet_length,
+    })
+  }
+}
@@ -1,169 +1,560 @@
 use crate::baseblock::BaseBlock;
+use crate::decryption_secrets::DecryptionSecrets;
 use crate::enhanced_packet::EnhancedPacket;
+use crate::gap::Gap;
 use crate::interface_description::InterfaceDescription;
+use crate::interface_statistics::InterfaceStatistics;
+use crate::legacy_packet::LegacyPacket;
 use crate::loader::Config;
+use crate::name_resolution::NameResolution;
+use crate::pcap;
+use crate::pcap_header::PcapHeader;
+use crate::section::Section;
 use crate::section_header::SectionHeader;
+use crate::simple_packet::SimplePacket;
 use crate::types::BlockTypes;
-use crate::util::div_ceil;
-use ratatui::{buffer::Buffer, layout::Rect};
-use ratatui::{
-  style::{Color, Stylize},
-  text::{Line, Span},
-  widgets::{Paragraph, Widget, Wrap},
-};
-
-#[derive(Eq, PartialEq)]
+use crate::util::read_u32;
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
 pub enum BlockErrorKind {
   None,
   ZeroLength,
+  Truncated,
+  LengthMismatch,
+  TrailingLengthMismatch,
+  UnknownInterface,
+  UnknownLinkType,
+  Misaligned,
+  BadMagic,
+  UnknownVersion,
+  SnapLengthExceeded,
+  OriginalLengthExceeded,
+  MissingEndOfOptions,
+  UnknownBlockType,
+}
+
+impl BlockErrorKind {
+  pub fn description(&self) -> &'static str {
+    match self {
+      BlockErrorKind::None => "",
+      BlockErrorKind::ZeroLength => "Block has zero length",
+      BlockErrorKind::Truncated => "Block extends past the end of the file",
+      BlockErrorKind::LengthMismatch => "Block length does not match its contents",
+      BlockErrorKind::TrailingLengthMismatch => {
+        "Trailing block length does not match leading block length"
+      }
+      BlockErrorKind::UnknownInterface => "Interface ID has no Interface Description Block",
+      BlockErrorKind::UnknownLinkType => "Unknown link type",
+      BlockErrorKind::Misaligned => "Block length is not a multiple of 4",
+      BlockErrorKind::BadMagic => "Byte-order magic is not 0x1a2b3c4d in either byte order",
+      BlockErrorKind::UnknownVersion => "Unsupported format version",
+      BlockErrorKind::SnapLengthExceeded => {
+        "Captured length is greater than the interface snap length"
+      }
+      BlockErrorKind::OriginalLengthExceeded => {
+        "Captured length is greater than the original length"
+      }
+      BlockErrorKind::MissingEndOfOptions => "Options are not terminated by opt_endofopt",
+      BlockErrorKind::UnknownBlockType => "Unknown block type",
+    }
+  }
+}
+
+#[derive(Clone)]
+pub struct Interface {
+  pub link_type: u16,
+  pub snap_length: u32,
+  pub name: Option<String>,
+  // Raw if_tsresol value, where 6 means microseconds
+  pub timestamp_resolution: u8,
+}
+
+impl Interface {
+  // Converts a timestamp in units of the interface's resolution to time since the epoch
+  pub fn timestamp(&self, ticks: u64) -> Duration {
+    let exponent = (self.timestamp_resolution & 0x7f) as u32;
+    // The most significant bit selects a power of 2 rather than a power of 10
+    let nanoseconds = if self.timestamp_resolution & 0x80 == 0 {
+      10u128
+        .checked_pow(exponent)
+        .map_or(0, |units| ticks as u128 * 1_000_000_000 / units)
+    } else {
+      (ticks as u128 * 1_000_000_000) >> exponent
+    };
+    Duration::from_nanos(nanoseconds.min(u64::MAX as u128) as u64)
+  }
+}
+
+// Fields shared by every kind of packet block
+pub struct PacketInfo {
+  pub interface_id: u32,
+  // In units of the interface's timestamp resolution, and absent from Simple Packet Blocks
+  pub timestamp: Option<u64>,
+  pub captured_length: u32,
+  pub original_length: u32,
+}
+
+// Interfaces and names defined so far in a section. It is only ever appended to, so every block
+// in the section shares one store and remembers how much of it had been filled in.
+#[derive(Default)]
+struct SectionStore {
+  interfaces: Vec<Interface>,
+  // Names from Name Resolution Blocks keyed by raw address bytes, each with its position among
+  // all the names added to the section
+  names: HashMap<Vec<u8>, Vec<(usize, String)>>,
+  name_count: usize,
+}
+
+// State shared by every block in a section, reset by each Section Header Block
+#[derive(Clone)]
+pub struct SectionContext {
+  pub index: u32,
+  pub little_endian: bool,
+  store: Arc<RwLock<SectionStore>>,
+  // How much of the store a block can see, which is what was defined before it
+  interface_count: usize,
+  name_count: usize,
+}
+
+impl SectionContext {
+  pub fn new(index: u32, little_endian: bool) -> SectionContext {
+    SectionContext {
+      index,
+      little_endian,
+      store: Arc::default(),
+      interface_count: 0,
+      name_count: 0,
+    }
+  }
+
+  pub fn interface(&self, interface_id: u32) -> Option<Interface> {
+    if interface_id as usize >= self.interface_count {
+      return None;
+    }
+    let store = self.store.read().unwrap();
+    store.interfaces.get(interface_id as usize).cloned()
+  }
+
+  // Every name given to an address, in the order they were defined
+  pub fn name(&self, address: &[u8]) -> Option<String> {
+    let store = self.store.read().unwrap();
+    let names: Vec<&str> = store
+      .names
+      .get(address)?
+      .iter()
+      .filter(|(position, _)| *position < self.name_count)
+      .map(|(_, name)| name.as_str())
+      .collect();
+    (!names.is_empty()).then(|| names.join(", "))
+  }
+
+  fn add_interface(&mut self, interface: Interface) {
+    self.store.write().unwrap().interfaces.push(interface);
+    self.interface_count += 1;
+  }
+
+  // Ignores names an address has already been given
+  fn add_name(&mut self, address: Vec<u8>, name: String) {
+    let mut store = self.store.write().unwrap();
+    let position = store.name_count;
+    let names = store.names.entry(address).or_default();
+    if names.iter().any(|(_, n)| *n == name) {
+      return;
+    }
+    names.push((position, name));
+    store.name_count += 1;
+    self.name_count = store.name_count;
+  }
 }
 
 pub trait PngBlock {
   fn rows(&self, width: u16) -> u16;
-  fn sections(&self) -> Vec<(String, usize)>;
+  // Covers the whole block, laid out in order
+  fn sections(&self) -> Vec<Section>;
   fn error(&self) -> &BlockErrorKind;
+  // Every problem found while decoding, where error only holds the first
+  fn errors(&self) -> &[BlockErrorKind];
   fn id(&self) -> u32;
+  fn section(&self) -> u32;
   fn length(&self) -> usize;
   fn block_type(&self) -> &BlockTypes;
   fn title_line(&self) -> String;
   fn raw(&self) -> &Vec<u8>;
+  // Interface, timestamp and lengths of a block holding a packet
+  fn packet(&self) -> Option<PacketInfo> {
+    None
+  }
 }
 
-fn box_up<T>(t: (T, usize)) -> (Box<dyn PngBlock>, usize)
+fn box_up<T>(
+  result: Result<(T, usize), BlockErrorKind>,
+  data: &[u8],
+  id: u32,
+  section: &SectionContext,
+) -> Box<dyn PngBlock>
 where
   T: PngBlock + 'static,
 {
-  (Box::new(t.0), t.1)
+  match result {
+    Ok(t) => Box::new(t.0),
+    // Fall back to showing the undecoded block with the error
+    Err(error) => {
+      let mut base = BaseBlock::parse(data, id, section);
+      base.0.error_ = error;
+      base.0.errors_.insert(0, error);
+      Box::new(base.0)
+    }
+  }
 }
 
-pub fn parse(data: &Vec<u8>, config: Config) -> Vec<Box<dyn PngBlock>> {
-  let mut out: Vec<Box<dyn PngBlock>> = vec![];
-  let mut interfaces: Vec<u16> = vec![];
-  let mut pos: usize = 0;
-  let mut id: u32 = 0;
-  while pos < data.len() {
-    let block_type: BlockTypes = u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap()).into();
-    let single: (Box<dyn PngBlock>, usize);
-    match block_type {
-      BlockTypes::EnhancedPacketBlock => {
-        single = box_up(EnhancedPacket::parse(
-          &data[pos..],
-          id,
-          &interfaces,
-          &config,
-        ))
+// Finds the next position that holds a known block type with consistent leading and trailing
+// lengths, or the end of the data if there is none
+fn resync(data: &[u8], from: usize, little_endian: bool) -> usize {
+  let mut pos = from;
+  while pos + BaseBlock::SIZE <= data.len() {
+    let block_type: BlockTypes = read_u32(&data[pos..], little_endian).into();
+    let little_endian = match block_type {
+      BlockTypes::SectionHeaderBlock => SectionHeader::byte_order(&data[pos..]),
+      _ => little_endian,
+    };
+    if !matches!(block_type, BlockTypes::Unknown | BlockTypes::Reserved)
+      && BaseBlock::check(&data[pos..], little_endian) == BlockErrorKind::None
+    {
+      return pos;
+    }
+    pos += 4;
+  }
+  data.len()
+}
+
+// How an indexed block is decoded once it is needed
+#[derive(Clone, Copy)]
+pub enum EntryKind {
+  Block,
+  // Bytes skipped while resynchronizing, with the framing error that caused it
+  Gap(BlockErrorKind),
+  // Block with the framing error that ended the scan
+  Corrupt(BlockErrorKind),
+  PcapHeader,
+  PcapRecord { nanoseconds: bool },
+}
+
+// Where a block is in the file, and the section state needed to decode it
+pub struct BlockEntry {
+  pub offset: usize,
+  pub length: usize,
+  pub kind: EntryKind,
+  pub section: SectionContext,
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+  Pcapng,
+  Pcap { nanoseconds: bool },
+}
+
+// Indexes a capture from block headers, only decoding the blocks that later blocks depend on
+#[derive(Clone)]
+pub struct Scanner {
+  pos: usize,
+  // Detected from the first bytes of the file
+  format: Option<Format>,
+  section: SectionContext,
+  section_count: u32,
+  resync: bool,
+  // Whether a block cut short at the end of the data may still be written
+  follow: bool,
+  done: bool,
+}
+
+impl Scanner {
+  pub fn new(resync: bool, follow: bool) -> Scanner {
+    Scanner {
+      pos: 0,
+      format: None,
+      section: SectionContext::new(0, true),
+      section_count: 0,
+      resync,
+      follow,
+      done: false,
+    }
+  }
+
+  // Reports blocks cut short at the end of the data as truncated, once nothing more is coming
+  pub fn stop_following(&mut self) {
+    self.follow = false;
+  }
+
+  // Offset of the first byte that has not been indexed
+  pub fn pos(&self) -> usize {
+    self.pos
+  }
+
+  pub fn next(&mut self, data: &[u8], config: &Config) -> Option<BlockEntry> {
+    if self.done || self.pos >= data.len() {
+      return None;
+    }
+    let format = match self.format {
+      Some(format) => format,
+      // Wait for the magic number of a file that is still being written
+      None if self.follow && data.len() < 4 => return None,
+      None => {
+        let format = match pcap::byte_order(data) {
+          Some(little_endian) => {
+            self.section = SectionContext::new(0, little_endian);
+            Format::Pcap { nanoseconds: false }
+          }
+          None => Format::Pcapng,
+        };
+        self.format = Some(format);
+        format
+      }
+    };
+    let entry = match format {
+      Format::Pcapng => self.next_block(data, config),
+      Format::Pcap { nanoseconds } => self.next_record(data, config, nanoseconds),
+    }?;
+    self.pos += entry.length;
+    Some(entry)
+  }
+
+  fn entry(&self, length: usize, kind: EntryKind) -> BlockEntry {
+    BlockEntry {
+      offset: self.pos,
+      length,
+      kind,
+      section: self.section.clone(),
+    }
+  }
+
+  fn next_block(&mut self, data: &[u8], config: &Config) -> Option<BlockEntry> {
+    let block_data = &data[self.pos..];
+    // The Section Header Block type is a palindrome so it reads the same in either byte order
+    let block_type: BlockTypes = match block_data.get(..4) {
+      Some(b) => read_u32(b, self.section.little_endian).into(),
+      None => BlockTypes::Unknown,
+    };
+    let little_endian = match block_type {
+      BlockTypes::SectionHeaderBlock => SectionHeader::byte_order(block_data),
+      _ => self.section.little_endian,
+    };
+    // Without a usable length the next block can only be found by scanning for it
+    let framing_error = BaseBlock::check(block_data, little_endian);
+    if framing_error == BlockErrorKind::Truncated && self.follow {
+      return None;
+    }
+    if framing_error != BlockErrorKind::None
+      && framing_error != BlockErrorKind::TrailingLengthMismatch
+    {
+      if !self.resync {
+        self.done = true;
+        // Keep at least the type and length fields so there is something to show the error on
+        let length = BaseBlock::framed_length(block_data, framing_error, little_endian)
+          .max(8)
+          .min(block_data.len());
+        return Some(self.entry(length, EntryKind::Corrupt(framing_error)));
+      }
+      let next = resync(data, self.pos + 4, self.section.little_endian);
+      if next == data.len() && self.follow {
+        // The next valid block may not have been written yet
+        return None;
       }
+      return Some(self.entry(next - self.pos, EntryKind::Gap(framing_error)));
+    }
+    let length = read_u32(&block_data[4..8], little_endian) as usize;
+    let block_data = &block_data[..length];
+    match block_type {
       BlockTypes::InterfaceDescriptionBlock => {
-        let ifd = InterfaceDescription::parse(&data[pos..], id, &config);
-        interfaces.push(ifd.0.link_type);
-        single = box_up(ifd);
+        if let Ok(ifd) = InterfaceDescription::parse(block_data, 0, &self.section, config) {
+          let entry = self.entry(length, EntryKind::Block);
+          self.section.add_interface(Interface {
+            link_type: ifd.0.link_type,
+            snap_length: ifd.0.snap_length,
+            name: ifd.0.name,
+            timestamp_resolution: ifd.0.timestamp_resolution,
+          });
+          return Some(entry);
+        }
+      }
+      BlockTypes::NameResolutionBlock => {
+        if let Ok(nrb) = NameResolution::parse(block_data, 0, &self.section) {
+          let entry = self.entry(length, EntryKind::Block);
+          for (address, name) in nrb.0.names {
+            self.section.add_name(address, name);
+          }
+          return Some(entry);
+        }
+      }
+      BlockTypes::SectionHeaderBlock => {
+        // Interface IDs are only meaningful within the section that defined them
+        self.section = SectionContext::new(self.section_count, little_endian);
+        self.section_count += 1;
       }
-      BlockTypes::SectionHeaderBlock => single = box_up(SectionHeader::parse(&data[pos..], id)),
-      _ => single = box_up(BaseBlock::parse(&data[pos..], id)),
-    }
-    if single.0.error() != &BlockErrorKind::None {
-      out.push(single.0);
-      break;
-    }
-    out.push(single.0);
-    pos += single.1;
-    id += 1;
-  }
-  out
-}
-
-pub fn draw_block(
-  block: &Box<dyn PngBlock>,
-  mut area: Rect,
-  buf: &mut Buffer,
-  hidden: u16,
-  folded: bool,
-  ascii: bool,
-) -> u16 {
-  if block.error() == &BlockErrorKind::ZeroLength {
-    Line::raw(block.id().to_string() + ": ERROR Block has zero length")
-      .underlined()
-      .bold()
-      .render(area, buf);
-  }
-
-  let bytes_in_row = (area.width + 1) / 3;
-  let total_rows = div_ceil(block.length() as u16, bytes_in_row) + 1;
-  assert!(hidden < total_rows);
-
-  let total_rows_to_print = std::cmp::min(total_rows - hidden, area.height);
-  if total_rows_to_print == 0 {
-    return 0;
-  }
-  let mut rows_to_print = total_rows_to_print;
-
-  Line::raw(block.title_line())
-    .underlined()
-    .bold()
-    .render(area, buf);
-  if folded || rows_to_print == 1 {
-    return 1;
-  }
-
-  area.y += 1;
-  area.height -= 1;
-  rows_to_print -= 1;
-
-  let print_bytes: fn(&[u8]) -> String = if ascii { to_ascii } else { to_hex };
-
-  let start: usize = (hidden * bytes_in_row) as usize;
-  let end: usize = std::cmp::min(
-    (hidden + rows_to_print) * bytes_in_row,
-    block.length() as u16,
-  ) as usize;
-  let mut spans = vec![];
-  let mut current_section = 0;
-  let mut index = 0;
-  let mut fg_colour_index = 0;
-  let mut bg_colour_index = 0;
-  let fg_colours = [
-    Color::White,
-    Color::Red,
-    Color::Green,
-    Color::Magenta,
-    Color::LightBlue,
-  ];
-  let bg_colours = [Color::Black, Color::DarkGray];
-  while index < end {
-    let section = block.sections()[current_section].1;
-    if index < start && index + section > start {
-      spans.push(
-        Span::raw(print_bytes(&block.raw()[start..index + section]))
-          .fg(fg_colours[fg_colour_index])
-          .bg(bg_colours[bg_colour_index]),
-      );
-    } else if index >= start && index + section <= end {
-      spans.push(
-        Span::raw(print_bytes(&block.raw()[index..index + section]))
-          .fg(fg_colours[fg_colour_index])
-          .bg(bg_colours[bg_colour_index]),
-      );
-    } else if index < end && index + section > end {
-      spans.push(
-        Span::raw(print_bytes(&block.raw()[index..end]))
-          .fg(fg_colours[fg_colour_index])
-          .bg(bg_colours[bg_colour_index]),
-      );
-    }
-    fg_colour_index = (fg_colour_index + 1) % fg_colours.len();
-    bg_colour_index = (bg_colour_index + 1) % bg_colours.len();
-    index += section;
-    current_section += 1;
-  }
-
-  for i in 0..spans.len() {
-    spans.insert(2 * i + 1, Span::raw(" "));
-  }
-
-  Paragraph::new(Line::from(spans))
-    .wrap(Wrap { trim: true })
-    .render(area, buf);
-  total_rows_to_print
+      _ => (),
+    }
+    Some(self.entry(length, EntryKind::Block))
+  }
+
+  fn next_record(&mut self, data: &[u8], config: &Config, nanoseconds: bool) -> Option<BlockEntry> {
+    let rest = &data[self.pos..];
+    if self.pos == 0 {
+      let Ok(header) = PcapHeader::parse(rest, 0, &self.section, config) else {
+        if self.follow {
+          return None;
+        }
+        self.done = true;
+        return Some(self.entry(rest.len(), EntryKind::PcapHeader));
+      };
+      let entry = self.entry(header.1, EntryKind::PcapHeader);
+      self.format = Some(Format::Pcap {
+        nanoseconds: header.0.nanoseconds,
+      });
+      // A libpcap file has a single implicit interface described by its header
+      self.section.add_interface(Interface {
+        link_type: header.0.link_type,
+        snap_length: header.0.snap_length,
+        name: None,
+        timestamp_resolution: if header.0.nanoseconds { 9 } else { 6 },
+      });
+      return Some(entry);
+    }
+    let length = match pcap::record_length(rest, self.section.little_endian) {
+      Some(length) => length,
+      None if self.follow => return None,
+      None => {
+        self.done = true;
+        rest.len()
+      }
+    };
+    Some(self.entry(length, EntryKind::PcapRecord { nanoseconds }))
+  }
+}
+
+// Decodes an indexed block from the data it was scanned from
+// Returns None when the entry does not lie within data
+pub fn decode(
+  data: &[u8],
+  entry: &BlockEntry,
+  id: u32,
+  config: &Config,
+) -> Option<Box<dyn PngBlock>> {
+  let block_data = data.get(entry.offset..entry.offset.checked_add(entry.length)?)?;
+  Some(decode_block(block_data, entry, id, config))
+}
+
+fn decode_block(
+  block_data: &[u8],
+  entry: &BlockEntry,
+  id: u32,
+  config: &Config,
+) -> Box<dyn PngBlock> {
+  let section = &entry.section;
+  match entry.kind {
+    EntryKind::Block => (),
+    EntryKind::Gap(error) => return Box::new(Gap::new(block_data, id, error, section)),
+    EntryKind::Corrupt(error) => {
+      let block_type: BlockTypes = match block_data.get(..4) {
+        Some(b) => read_u32(b, section.little_endian).into(),
+        None => BlockTypes::Unknown,
+      };
+      let little_endian = match block_type {
+        BlockTypes::SectionHeaderBlock => SectionHeader::byte_order(block_data),
+        _ => section.little_endian,
+      };
+      return Box::new(BaseBlock::new(
+        block_data.to_vec(),
+        block_type,
+        block_data.len() as u32,
+        vec![],
+        id,
+        error,
+        &SectionContext::new(section.index, little_endian),
+      ));
+    }
+    EntryKind::PcapHeader | EntryKind::PcapRecord { .. } => {
+      return pcap::decode(block_data, entry.kind, id, section, config)
+    }
+  }
+  let block_type: BlockTypes = read_u32(block_data, section.little_endian).into();
+  match block_type {
+    BlockTypes::EnhancedPacketBlock => box_up(
+      EnhancedPacket::parse(block_data, id, section, config),
+      block_data,
+      id,
+      section,
+    ),
+    BlockTypes::PacketBlock => box_up(
+      LegacyPacket::parse(block_data, id, section, config),
+      block_data,
+      id,
+      section,
+    ),
+    BlockTypes::SimplePacketBlock => box_up(
+      SimplePacket::parse(block_data, id, section, config),
+      block_data,
+      id,
+      section,
+    ),
+    BlockTypes::InterfaceDescriptionBlock => box_up(
+      InterfaceDescription::parse(block_data, id, section, config),
+      block_data,
+      id,
+      section,
+    ),
+    BlockTypes::DecryptionSecretsBlock => box_up(
+      DecryptionSecrets::parse(block_data, id, section),
+      block_data,
+      id,
+      section,
+    ),
+    BlockTypes::InterfaceStatisticsBlock => box_up(
+      InterfaceStatistics::parse(block_data, id, section, config),
+      block_data,
+      id,
+      section,
+    ),
+    BlockTypes::NameResolutionBlock => box_up(
+      NameResolution::parse(block_data, id, section),
+      block_data,
+      id,
+      section,
+    ),
+    BlockTypes::SectionHeaderBlock => box_up(
+      SectionHeader::parse(block_data, id, section.index),
+      block_data,
+      id,
+      section,
+    ),
+    _ => {
+      let mut base = BaseBlock::parse(block_data, id, section).0;
+      if matches!(base.block_type_, BlockTypes::Unknown | BlockTypes::Reserved) {
+        base.flag(BlockErrorKind::UnknownBlockType);
+      }
+      Box::new(base)
+    }
+  }
+}
+
+// Decodes every block of a complete capture held in memory. Problems within the capture are
+// reported on the blocks they affect, so this only fails if the data is not a capture at all.
+pub fn parse(data: &[u8], config: &Config, resync: bool) -> io::Result<Vec<Box<dyn PngBlock>>> {
+  let is_pcapng = data.get(..4) == Some(&[0x0a, 0x0d, 0x0d, 0x0a]);
+  if !is_pcapng && pcap::byte_order(data).is_none() {
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidData,
+      "not a pcap or pcapng capture",
+    ));
+  }
+  let mut scanner = Scanner::new(resync, false);
+  let mut blocks: Vec<Box<dyn PngBlock>> = vec![];
+  while let Some(entry) = scanner.next(data, config) {
+    blocks.extend(decode(data, &entry, blocks.len() as u32, config));
+  }
+  Ok(blocks)
 }
 
 pub fn to_hex(s: &[u8]) -> String {
@@ -186,3 +577,185 @@ pub fn to_ascii(s: &[u8]) -> String {
     .collect::<Vec<_>>()
     .join(" ")
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{loader::builtin_config, types::block_type_str};
+
+  // A little-endian Section Header, Ethernet Interface Description and Enhanced Packet Block
+  fn capture() -> Vec<u8> {
+    let mut data = vec![];
+    for word in [0x0a0d0d0a, 28, 0x1a2b3c4d, 1, 0xffffffff, 0xffffffff, 28] {
+      data.extend(u32::to_le_bytes(word));
+    }
+    for word in [1, 20, 1, 0, 20] {
+      data.extend(u32::to_le_bytes(word));
+    }
+    for word in [6, 36, 0, 0, 1_000_000, 4, 4, 0xddccbbaa, 36] {
+      data.extend(u32::to_le_bytes(word));
+    }
+    data
+  }
+
+  #[test]
+  fn parse_valid() {
+    let blocks = parse(&capture(), &builtin_config().unwrap(), true).unwrap();
+    let block_types: Vec<_> = blocks
+      .iter()
+      .map(|b| block_type_str(b.block_type()))
+      .collect();
+    assert_eq!(
+      block_types,
+      [
+        "Section Header Block",
+        "Interface Description Block",
+        "Enhanced Packet Block",
+      ]
+    );
+    assert!(blocks.iter().all(|b| *b.error() == BlockErrorKind::None));
+    let packet = blocks[2].packet().unwrap();
+    assert_eq!(packet.timestamp, Some(1_000_000));
+    assert_eq!(packet.captured_length, 4);
+  }
+
+  #[test]
+  fn parse_truncated() {
+    let data = capture();
+    let blocks = parse(&data[..data.len() - 8], &builtin_config().unwrap(), true).unwrap();
+    assert_eq!(blocks.len(), 3);
+    assert_eq!(*blocks[1].error(), BlockErrorKind::None);
+    assert_eq!(*blocks[2].error(), BlockErrorKind::Truncated);
+  }
+
+  // Every entry the scanner finds in the data
+  fn scan(data: &[u8], resync: bool) -> Vec<BlockEntry> {
+    let config = builtin_config().unwrap();
+    let mut scanner = Scanner::new(resync, false);
+    std::iter::from_fn(|| scanner.next(data, &config)).collect()
+  }
+
+  fn lengths(entries: &[BlockEntry]) -> Vec<usize> {
+    entries.iter().map(|e| e.length).collect()
+  }
+
+  // Capture with bytes that are not a block between the Interface Description and the packet
+  fn capture_with_garbage() -> Vec<u8> {
+    let mut data = capture();
+    data.splice(48..48, [0xee; 8]);
+    data
+  }
+
+  #[test]
+  fn resync_skips_garbage() {
+    let data = capture_with_garbage();
+    let entries = scan(&data, true);
+    assert_eq!(lengths(&entries), [28, 20, 8, 36]);
+    assert_eq!(entries[3].offset, 56);
+    assert!(matches!(
+      entries[2].kind,
+      EntryKind::Gap(BlockErrorKind::Truncated)
+    ));
+    let blocks = parse(&data, &builtin_config().unwrap(), true).unwrap();
+    assert!(matches!(blocks[2].block_type(), BlockTypes::UnparsedGap));
+    assert_eq!(*blocks[3].error(), BlockErrorKind::None);
+  }
+
+  #[test]
+  fn resync_gap_to_end_of_data() {
+    let mut data = capture();
+    data.extend([0; 12]);
+    let entries = scan(&data, true);
+    assert_eq!(lengths(&entries), [28, 20, 36, 12]);
+    assert!(matches!(
+      entries[3].kind,
+      EntryKind::Gap(BlockErrorKind::ZeroLength)
+    ));
+  }
+
+  #[test]
+  fn without_resync_scan_stops() {
+    let data = capture_with_garbage();
+    let entries = scan(&data, false);
+    // Everything from the bad block on is left undecoded
+    assert_eq!(lengths(&entries), [28, 20, 44]);
+    assert!(matches!(
+      entries[2].kind,
+      EntryKind::Corrupt(BlockErrorKind::Truncated)
+    ));
+  }
+
+  #[test]
+  fn without_resync_zero_length() {
+    let mut data = capture();
+    data[32..36].copy_from_slice(&[0; 4]);
+    let entries = scan(&data, false);
+    assert_eq!(lengths(&entries), [28, 8]);
+    let blocks = parse(&data, &builtin_config().unwrap(), false).unwrap();
+    assert!(matches!(
+      blocks[1].block_type(),
+      BlockTypes::InterfaceDescriptionBlock
+    ));
+    assert_eq!(*blocks[1].error(), BlockErrorKind::ZeroLength);
+  }
+
+  #[test]
+  fn trailing_length_mismatch_keeps_framing() {
+    let mut data = capture();
+    data[44..48].copy_from_slice(&u32::to_le_bytes(24));
+    assert_eq!(lengths(&scan(&data, true)), [28, 20, 36]);
+    let blocks = parse(&data, &builtin_config().unwrap(), true).unwrap();
+    assert_eq!(*blocks[1].error(), BlockErrorKind::TrailingLengthMismatch);
+    assert_eq!(*blocks[2].error(), BlockErrorKind::None);
+  }
+
+  #[test]
+  fn parse_wrong_magic() {
+    let mut data = capture();
+    data[0] = 0;
+    let error = parse(&data, &builtin_config().unwrap(), true)
+      .err()
+      .unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+  }
+
+  #[test]
+  fn section_names() {
+    let mut section = SectionContext::new(0, true);
+    section.add_name(vec![10, 0, 0, 1], "gateway".to_owned());
+    let earlier = section.clone();
+    section.add_name(vec![10, 0, 0, 1], "gateway".to_owned());
+    section.add_name(vec![10, 0, 0, 1], "router".to_owned());
+    assert_eq!(
+      section.name(&[10, 0, 0, 1]).as_deref(),
+      Some("gateway, router")
+    );
+    // Blocks only see names defined before them
+    assert_eq!(earlier.name(&[10, 0, 0, 1]).as_deref(), Some("gateway"));
+    assert_eq!(section.name(&[10, 0, 0, 2]), None);
+  }
+
+  #[test]
+  fn timestamp_resolution() {
+    let interface = |timestamp_resolution| Interface {
+      link_type: 1,
+      snap_length: 0,
+      name: None,
+      timestamp_resolution,
+    };
+    // Microseconds by default
+    assert_eq!(
+      interface(6).timestamp(1_500_000),
+      Duration::from_millis(1500)
+    );
+    assert_eq!(
+      interface(9).timestamp(1_500_000),
+      Duration::from_micros(1500)
+    );
+    // Powers of 2, here 1/1024 of a second
+    assert_eq!(interface(0x8a).timestamp(512), Duration::from_millis(500));
+    // Resolutions too fine to count in nanoseconds
+    assert_eq!(interface(30).timestamp(1), Duration::ZERO);
+    assert_eq!(interface(100).timestamp(1), Duration::ZERO);
+  }
+}
@@ -0,0 +1,266 @@
+use std::{
+  fmt,
+  net::{IpAddr, Ipv4Addr},
+  time::Duration,
+};
+
+// What the bytes of a section decode to
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+  // Nothing decoded, such as padding or packet data with no fields configured
+  None,
+  Uint(u64),
+  Int(i64),
+  // A number shown in hex, such as flags
+  Hex(u64),
+  // A number and what it means, such as a block or link type
+  Enum(u64, String),
+  // Time since the epoch
+  Timestamp(Duration),
+  String(String),
+  Bytes(Vec<u8>),
+  Address(Address),
+}
+
+// An address from an option or a Name Resolution Block record
+#[derive(Clone, Debug, PartialEq)]
+pub enum Address {
+  Ip(IpAddr, Option<Mask>),
+  // MAC or EUI
+  Hardware(Vec<u8>),
+}
+
+// What follows an interface's IP address in its options
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mask {
+  Netmask(Ipv4Addr),
+  PrefixLength(u8),
+}
+
+impl fmt::Display for Value {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Value::None => Ok(()),
+      Value::Uint(v) => write!(f, "{}", v),
+      Value::Int(v) => write!(f, "{}", v),
+      Value::Hex(v) => write!(f, "{:#010x}", v),
+      Value::Enum(_, label) => write!(f, "{}", label),
+      Value::Timestamp(t) => write!(f, "{}.{:09}", t.as_secs(), t.subsec_nanos()),
+      Value::String(s) => write!(f, "{}", s),
+      Value::Bytes(b) => {
+        write!(f, "0x")?;
+        b.iter().try_for_each(|b| write!(f, "{:02x}", b))
+      }
+      Value::Address(a) => write!(f, "{}", a),
+    }
+  }
+}
+
+impl fmt::Display for Address {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Address::Ip(address, None) => write!(f, "{}", address),
+      Address::Ip(address, Some(Mask::Netmask(mask))) => write!(f, "{}/{}", address, mask),
+      Address::Ip(address, Some(Mask::PrefixLength(length))) => write!(f, "{}/{}", address, length),
+      Address::Hardware(bytes) => {
+        let octets: Vec<_> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        write!(f, "{}", octets.join(":"))
+      }
+    }
+  }
+}
+
+// A named range of a block's bytes and what it decodes to
+#[derive(Clone, Debug)]
+pub struct Section {
+  pub name: String,
+  // From the start of the block
+  pub offset: usize,
+  pub length: usize,
+  pub value: Value,
+  pub little_endian: bool,
+  // Shown as ASCII rather than hex
+  pub text: bool,
+  // Holds a value that contradicts the rest of the block
+  pub invalid: bool,
+  // Holds the captured packet, split into the fields configured for its link type
+  pub packet_data: bool,
+  // Smaller sections that make this one up, covering it exactly
+  pub children: Vec<Section>,
+}
+
+impl Section {
+  // Placed once the whole block is built, by place
+  pub fn new(name: impl Into<String>, length: usize, value: Value) -> Section {
+    Section {
+      name: name.into(),
+      offset: 0,
+      length,
+      value,
+      little_endian: true,
+      text: false,
+      invalid: false,
+      packet_data: false,
+      children: vec![],
+    }
+  }
+
+  // A section made up of others
+  pub fn group(name: impl Into<String>, value: Value, children: Vec<Section>) -> Section {
+    Section {
+      length: children.iter().map(|c| c.length).sum(),
+      children,
+      ..Section::new(name, 0, value)
+    }
+  }
+
+  pub fn as_text(mut self) -> Section {
+    self.text = true;
+    self
+  }
+
+  pub fn as_invalid(mut self) -> Section {
+    self.invalid = true;
+    self
+  }
+
+  pub fn as_packet_data(mut self) -> Section {
+    self.packet_data = true;
+    self
+  }
+
+  // Name and value, as shown in the detail pane
+  pub fn description(&self) -> String {
+    match self.value {
+      Value::None => self.name.clone(),
+      _ => format!("{} - {}", self.name, self.value),
+    }
+  }
+
+  // The section's bytes within the raw block
+  pub fn raw<'a>(&self, block: &'a [u8]) -> &'a [u8] {
+    &block[self.offset..self.offset + self.length]
+  }
+
+  // Lays sections out one after another from offset, in the byte order of their block
+  pub fn place(sections: &mut [Section], mut offset: usize, little_endian: bool) {
+    for section in sections {
+      section.offset = offset;
+      section.little_endian = little_endian;
+      Self::place(&mut section.children, offset, little_endian);
+      offset += section.length;
+    }
+  }
+
+  // The sections that are not made up of others, in order
+  pub fn leaves(sections: &[Section]) -> Vec<&Section> {
+    let mut leaves = vec![];
+    for section in sections {
+      if section.children.is_empty() {
+        leaves.push(section);
+      } else {
+        leaves.extend(Self::leaves(&section.children));
+      }
+    }
+    leaves
+  }
+
+  // Whether a group's value tells more than its children's do, such as a timestamp put together
+  // from two halves
+  pub fn adds_value(&self) -> bool {
+    !self.children.is_empty()
+      && self.value != Value::None
+      && self.children.iter().all(|c| c.value != self.value)
+  }
+
+  // The leaf holding the byte at offset, with the innermost group around it that adds a value
+  pub fn find(sections: &[Section], offset: usize) -> Option<(&Section, Option<&Section>)> {
+    let section = sections.iter().find(|s| offset < s.offset + s.length)?;
+    if section.children.is_empty() {
+      return Some((section, None));
+    }
+    let (leaf, group) = Self::find(&section.children, offset)?;
+    Some((leaf, group.or(section.adds_value().then_some(section))))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn place_and_leaves() {
+    let mut sections = vec![
+      Section::new("Block Type", 4, Value::Uint(6)),
+      Section::group(
+        "Options",
+        Value::None,
+        vec![
+          Section::new("opt_comment Code", 2, Value::Uint(1)),
+          Section::new("opt_comment Length", 2, Value::Uint(3)),
+          Section::new("opt_comment", 3, Value::String("abc".to_owned())),
+        ],
+      ),
+      Section::new("Block Total Length", 4, Value::Uint(20)),
+    ];
+    Section::place(&mut sections, 8, false);
+
+    assert_eq!(sections[1].offset, 12);
+    assert_eq!(sections[1].length, 7);
+    assert_eq!(sections[2].offset, 19);
+    let leaves = Section::leaves(&sections);
+    let placed: Vec<_> = leaves
+      .iter()
+      .map(|s| (s.name.as_str(), s.offset, s.little_endian))
+      .collect();
+    assert_eq!(
+      placed,
+      [
+        ("Block Type", 8, false),
+        ("opt_comment Code", 12, false),
+        ("opt_comment Length", 14, false),
+        ("opt_comment", 16, false),
+        ("Block Total Length", 19, false),
+      ]
+    );
+  }
+
+  #[test]
+  fn find_with_group() {
+    let mut sections = vec![
+      Section::new("Block Type", 4, Value::Uint(6)),
+      Section::group(
+        "Timestamp",
+        Value::Timestamp(Duration::from_secs(1)),
+        vec![
+          Section::new("Timestamp Upper", 4, Value::Uint(0)),
+          Section::new("Timestamp Lower", 4, Value::Uint(1_000_000)),
+        ],
+      ),
+      Section::group(
+        "opt_comment",
+        Value::String("abc".to_owned()),
+        vec![
+          Section::new("opt_comment Code", 2, Value::Uint(1)),
+          Section::new("opt_comment", 3, Value::String("abc".to_owned())),
+        ],
+      ),
+    ];
+    Section::place(&mut sections, 0, true);
+    let found = |offset| {
+      Section::find(&sections, offset)
+        .map(|(leaf, group)| (leaf.name.as_str(), group.map(|g| g.name.as_str())))
+    };
+    assert_eq!(found(0), Some(("Block Type", None)));
+    assert_eq!(found(9), Some(("Timestamp Lower", Some("Timestamp"))));
+    // The comment's value is already shown by its text
+    assert_eq!(found(12), Some(("opt_comment Code", None)));
+    assert_eq!(found(17), None);
+  }
+
+  #[test]
+  fn leaves_of_empty_group() {
+    let sections = [Section::group("Options", Value::None, vec![])];
+    assert_eq!(Section::leaves(&sections).len(), 1);
+  }
+}
@@ -1,12 +1,15 @@
 use crate::{
   baseblock::BaseBlock,
-  pcapng::{BlockErrorKind, PngBlock},
+  options::option_sections,
+  pcapng::{BlockErrorKind, PngBlock, SectionContext},
+  section::{Section, Value},
   types::BlockTypes,
+  util::{read_u16, read_u32, read_u64},
 };
 
 pub struct SectionHeader {
   base: BaseBlock,
-  little_endian: bool,
+  pub little_endian: bool,
   major_version: u16,
   minor_version: u16,
   section_length: u64,
@@ -15,13 +18,37 @@ pub struct SectionHeader {
 impl SectionHeader {
   pub const SIZE: usize = BaseBlock::SIZE + 16;
 
-  pub fn parse(data: &[u8], id: u32) -> (SectionHeader, usize) {
-    let base = BaseBlock::parse(data, id);
-    let little_endian = u32::from_le_bytes(data[8..12].try_into().unwrap()) == 0x1a2b3c4d;
-    let major_version = u16::from_le_bytes(data[12..14].try_into().unwrap());
-    let minor_version = u16::from_le_bytes(data[14..16].try_into().unwrap());
-    let section_length = u64::from_le_bytes(data[16..24].try_into().unwrap());
-    (
+  // The byte-order magic decides how every other field in the section is read
+  pub fn byte_order(data: &[u8]) -> bool {
+    match data.get(8..12).map(|magic| read_u32(magic, true)) {
+      Some(0x4d3c2b1a) => false,
+      // A corrupt magic is guessed from whichever byte order gives consistent block lengths,
+      // preferring little endian
+      Some(magic) if magic != 0x1a2b3c4d => {
+        BaseBlock::check(data, true) == BlockErrorKind::None
+          || BaseBlock::check(data, false) != BlockErrorKind::None
+      }
+      _ => true,
+    }
+  }
+
+  pub fn parse(data: &[u8], id: u32, index: u32) -> Result<(SectionHeader, usize), BlockErrorKind> {
+    let little_endian = Self::byte_order(data);
+    let mut base = BaseBlock::parse(data, id, &SectionContext::new(index, little_endian));
+    if (base.0.length_ as usize) < Self::SIZE {
+      return Err(BlockErrorKind::LengthMismatch);
+    }
+    if read_u32(&data[8..12], little_endian) != 0x1a2b3c4d {
+      base.0.flag(BlockErrorKind::BadMagic);
+    }
+    let major_version = read_u16(&data[12..14], little_endian);
+    let minor_version = read_u16(&data[14..16], little_endian);
+    if major_version != 1 || minor_version != 0 {
+      base.0.flag(BlockErrorKind::UnknownVersion);
+    }
+    base.0.check_options(Self::SIZE - 4);
+    let section_length = read_u64(&data[16..24], little_endian);
+    Ok((
       SectionHeader {
         base: base.0,
         little_endian,
@@ -30,7 +57,7 @@ impl SectionHeader {
         section_length,
       },
       base.1,
-    )
+    ))
   }
 }
 
@@ -39,49 +66,48 @@ impl PngBlock for SectionHeader {
     self.base.rows(width)
   }
 
-  fn sections(&self) -> Vec<(String, usize)> {
-    let sections: Vec<(String, usize)> = vec![
-      (
-        "Section Byte Order - ".to_owned()
-          + if self.little_endian {
-            "Little Endian"
-          } else {
-            "Big Endian"
-          },
+  fn sections(&self) -> Vec<Section> {
+    let magic = read_u32(&self.raw()[8..12], self.little_endian);
+    let byte_order = if self.little_endian {
+      "Little Endian"
+    } else {
+      "Big Endian"
+    };
+    let mut sections = vec![
+      Section::new(
+        "Section Byte Order",
         4,
+        Value::Enum(magic as u64, byte_order.to_owned()),
       ),
-      (
-        "Major Version - ".to_owned() + &self.major_version.to_string(),
-        2,
-      ),
-      (
-        "Minor Version - ".to_owned() + &self.minor_version.to_string(),
-        2,
-      ),
-      (
-        "Section Length - ".to_owned() + &self.section_length.to_string(),
-        8,
-      ),
-      ("Options".to_owned(), self.base.length() - Self::SIZE),
+      Section::new("Major Version", 2, Value::Uint(self.major_version as u64)),
+      Section::new("Minor Version", 2, Value::Uint(self.minor_version as u64)),
+      Section::new("Section Length", 8, Value::Uint(self.section_length)),
     ];
-    let mut base_sections = self.base.sections();
-    base_sections.remove(2);
-    base_sections.splice(2..2, sections);
-    assert_eq!(
-      base_sections.iter().map(|s| s.1 as u32).sum::<u32>(),
-      self.base.length_
-    );
-    base_sections
+    sections.extend(option_sections(
+      &self.raw()[Self::SIZE - 4..self.length() - 4],
+      self.block_type(),
+      self.little_endian,
+      None,
+    ));
+    self.base.frame(sections)
   }
 
   fn error(&self) -> &BlockErrorKind {
     self.base.error()
   }
 
+  fn errors(&self) -> &[BlockErrorKind] {
+    self.base.errors()
+  }
+
   fn id(&self) -> u32 {
     self.base.id()
   }
 
+  fn section(&self) -> u32 {
+    self.base.section()
+  }
+
   fn length(&self) -> usize {
     self.base.length()
   }
@@ -0,0 +1,129 @@
+use crate::{
+  baseblock::BaseBlock,
+  loader::Config,
+  packet_data::{packet_data_section, resolve_link_type},
+  pcapng::{BlockErrorKind, PacketInfo, PngBlock, SectionContext},
+  section::{Section, Value},
+  types::BlockTypes,
+  util::read_u32,
+};
+
+pub struct SimplePacket {
+  base: BaseBlock,
+  original_packet_length: u32,
+  captured_packet_length: u32,
+  link_type: Option<u16>,
+  sections_: Vec<Section>,
+  link_type_str: String,
+}
+
+impl SimplePacket {
+  pub const SIZE: usize = BaseBlock::SIZE + 4;
+
+  pub fn parse(
+    data: &[u8],
+    id: u32,
+    section: &SectionContext,
+    config: &Config,
+  ) -> Result<(SimplePacket, usize), BlockErrorKind> {
+    let mut base = BaseBlock::parse(data, id, section);
+    if (base.0.length_ as usize) < Self::SIZE {
+      return Err(BlockErrorKind::LengthMismatch);
+    }
+    let original_packet_length = read_u32(&data[8..12], section.little_endian);
+    // Simple Packets always belong to the first interface, and are only truncated by its snap length
+    let mut captured_packet_length =
+      std::cmp::min(original_packet_length, base.0.length_ - Self::SIZE as u32);
+    if let Some(interface) = section.interface(0) {
+      if interface.snap_length != 0 {
+        captured_packet_length = std::cmp::min(captured_packet_length, interface.snap_length);
+      }
+    }
+    let (link_type, link_type_str) = resolve_link_type(0, section, config, &mut base.0);
+    let mut p = SimplePacket {
+      base: base.0,
+      original_packet_length,
+      captured_packet_length,
+      link_type,
+      sections_: vec![],
+      link_type_str,
+    };
+    p.sections_ = p.sections_impl(section, config);
+    if p.sections_.iter().map(|s| s.length).sum::<usize>() != p.length() {
+      return Err(BlockErrorKind::LengthMismatch);
+    }
+    Ok((p, base.1))
+  }
+
+  fn sections_impl(&self, section: &SectionContext, config: &Config) -> Vec<Section> {
+    let mut sections = vec![
+      Section::new(
+        "Original Packet Length",
+        4,
+        Value::Uint(self.original_packet_length as u64),
+      ),
+      packet_data_section(
+        &self.raw()[Self::SIZE - 4..Self::SIZE - 4 + self.captured_packet_length as usize],
+        self.link_type,
+        section,
+        config,
+      ),
+    ];
+    let padding = self.length() - Self::SIZE - self.captured_packet_length as usize;
+    if padding > 0 {
+      sections.push(Section::new("Padding", padding, Value::None));
+    }
+    self.base.frame(sections)
+  }
+}
+
+impl PngBlock for SimplePacket {
+  fn rows(&self, width: u16) -> u16 {
+    self.base.rows(width)
+  }
+
+  fn sections(&self) -> Vec<Section> {
+    self.sections_.clone()
+  }
+
+  fn error(&self) -> &BlockErrorKind {
+    self.base.error()
+  }
+
+  fn errors(&self) -> &[BlockErrorKind] {
+    self.base.errors()
+  }
+
+  fn id(&self) -> u32 {
+    self.base.id()
+  }
+
+  fn section(&self) -> u32 {
+    self.base.section()
+  }
+
+  fn length(&self) -> usize {
+    self.base.length()
+  }
+
+  fn block_type(&self) -> &BlockTypes {
+    &BlockTypes::SimplePacketBlock
+  }
+
+  fn title_line(&self) -> String {
+    self.base.title_line() + " - " + &self.link_type_str
+  }
+
+  fn raw(&self) -> &Vec<u8> {
+    self.base.raw()
+  }
+
+  fn packet(&self) -> Option<PacketInfo> {
+    Some(PacketInfo {
+      interface_id: 0,
+      timestamp: None,
+      captured_length: self.captured_packet_length,
+      original_length: self.original_packet_length,
+    })
+  }
+}
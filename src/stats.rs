@@ -0,0 +1,146 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use hexng::{capture::Capture, types::block_type_str};
+
+// Largest original length counted in each bucket of the size histogram, with a final bucket for
+// anything bigger
+const SIZE_BUCKETS: [u32; 9] = [19, 39, 79, 159, 319, 639, 1279, 2559, 5119];
+
+// Summary of a capture, gathered from its decoded blocks
+#[derive(Default)]
+pub struct Stats {
+  // Blocks added so far, as they can be gathered a batch at a time
+  pub counted: usize,
+  // In the order each type was first seen
+  block_types: Vec<(String, usize)>,
+  // Description and packet count, keyed by section and interface ID
+  interfaces: BTreeMap<(u32, u32), (String, usize)>,
+  link_types: BTreeMap<String, usize>,
+  block_bytes: u64,
+  captured_bytes: u64,
+  original_bytes: u64,
+  packets: usize,
+  truncated: usize,
+  first: Option<Duration>,
+  last: Option<Duration>,
+  sizes: [usize; SIZE_BUCKETS.len() + 1],
+}
+
+impl Stats {
+  // Adds up to limit more blocks from the capture, returning whether every indexed block has now
+  // been added
+  pub fn update(&mut self, capture: &Capture, limit: usize) -> bool {
+    let end = capture
+      .block_count()
+      .min(self.counted.saturating_add(limit));
+    for id in self.counted..end {
+      self.add(capture, id as u32);
+    }
+    self.counted = end;
+    end == capture.block_count()
+  }
+
+  fn add(&mut self, capture: &Capture, id: u32) {
+    let Some(block) = capture.decode(id) else {
+      return;
+    };
+    let block_type = block_type_str(block.block_type());
+    match self.block_types.iter_mut().find(|t| t.0 == block_type) {
+      Some(t) => t.1 += 1,
+      None => self.block_types.push((block_type, 1)),
+    }
+    self.block_bytes += block.length() as u64;
+    let Some(packet) = block.packet() else {
+      return;
+    };
+    let interface = capture
+      .context(id)
+      .and_then(|c| c.interface(packet.interface_id));
+    let link_type = match &interface {
+      Some(interface) => match capture.config().link_types.get(&interface.link_type) {
+        Some(link_type) => link_type.clone(),
+        None => format!("Unknown ({})", interface.link_type),
+      },
+      None => "Unknown Interface".to_owned(),
+    };
+    self
+      .interfaces
+      .entry((block.section(), packet.interface_id))
+      .or_insert_with(|| {
+        let name = match interface.as_ref().and_then(|i| i.name.as_ref()) {
+          Some(name) => format!("{}, {}", name, link_type),
+          None => link_type.clone(),
+        };
+        let description = format!(
+          "Section {} Interface {} ({})",
+          block.section(),
+          packet.interface_id,
+          name
+        );
+        (description, 0)
+      })
+      .1 += 1;
+    *self.link_types.entry(link_type).or_default() += 1;
+    self.packets += 1;
+    self.captured_bytes += packet.captured_length as u64;
+    self.original_bytes += packet.original_length as u64;
+    if packet.captured_length < packet.original_length {
+      self.truncated += 1;
+    }
+    if let (Some(ticks), Some(interface)) = (packet.timestamp, &interface) {
+      let time = interface.timestamp(ticks);
+      self.first = Some(self.first.map_or(time, |t| t.min(time)));
+      self.last = Some(self.last.map_or(time, |t| t.max(time)));
+    }
+    let bucket = SIZE_BUCKETS
+      .iter()
+      .position(|&b| packet.original_length <= b)
+      .unwrap_or(SIZE_BUCKETS.len());
+    self.sizes[bucket] += 1;
+  }
+
+  // The summary as text, with counts before names so they stay visible in a narrow pane
+  pub fn lines(&self) -> Vec<String> {
+    let row = |count: String, name: &str| format!("  {:>12}  {}", count, name);
+    let mut lines = vec!["Blocks".to_owned()];
+    for (block_type, count) in &self.block_types {
+      lines.push(row(count.to_string(), block_type));
+    }
+    lines.push("Packets per interface".to_owned());
+    for (description, count) in self.interfaces.values() {
+      lines.push(row(count.to_string(), description));
+    }
+    lines.push("Packets per link type".to_owned());
+    for (link_type, count) in &self.link_types {
+      lines.push(row(count.to_string(), link_type));
+    }
+    lines.push("Packets".to_owned());
+    lines.push(row(self.packets.to_string(), "Total"));
+    lines.push(row(self.truncated.to_string(), "Truncated"));
+    lines.push("Bytes".to_owned());
+    lines.push(row(self.block_bytes.to_string(), "Blocks"));
+    lines.push(row(self.captured_bytes.to_string(), "Captured"));
+    lines.push(row(self.original_bytes.to_string(), "Original"));
+    lines.push("Time".to_owned());
+    match (self.first, self.last) {
+      (Some(first), Some(last)) => {
+        let seconds = |d: Duration| format!("{}.{:09}", d.as_secs(), d.subsec_nanos());
+        lines.push(format!("  First     {}", seconds(first)));
+        lines.push(format!("  Last      {}", seconds(last)));
+        lines.push(format!("  Duration  {} s", seconds(last - first)));
+      }
+      _ => lines.push("  No timestamps".to_owned()),
+    }
+    lines.push("Packet sizes (original length)".to_owned());
+    let mut low = 0;
+    for (i, count) in self.sizes.iter().enumerate() {
+      let range = match SIZE_BUCKETS.get(i) {
+        Some(high) => format!("{}-{}", low, high),
+        None => format!("{}+", low),
+      };
+      lines.push(row(count.to_string(), &range));
+      low = SIZE_BUCKETS.get(i).map_or(0, |high| high + 1);
+    }
+    lines
+  }
+}
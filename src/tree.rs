@@ -0,0 +1,238 @@
+use std::{collections::HashSet, ops::Range};
+
+use ratatui::{
+  prelude::{Buffer, Rect},
+  style::{Style, Stylize},
+  text::Line,
+  widgets::{Block, Widget},
+};
+
+use hexng::section::Section;
+
+// A section as one line of the tree
+struct Row {
+  depth: usize,
+  // Child indices from the top level down
+  path: Vec<usize>,
+  // Section names from the top level down
+  names: Vec<String>,
+  group: bool,
+  description: String,
+  range: Range<usize>,
+}
+
+// Collapsible view of one block's sections, like the packet details in Wireshark
+#[derive(Default)]
+pub struct TreeView {
+  block: Option<u32>,
+  sections: Vec<Section>,
+  // Visible rows, rebuilt whenever the sections or expanded groups change
+  rows: Vec<Row>,
+  // Child indices from the top level down to the selected section
+  selected: Vec<usize>,
+  // Open groups, keyed by name so that they stay open when moving between blocks
+  expanded: HashSet<Vec<String>>,
+  scroll: usize,
+  // Keys move through the tree rather than the hex view
+  pub focused: bool,
+}
+
+impl TreeView {
+  // Shows the sections of block id, selecting the deepest section holding offset when given
+  pub fn update(&mut self, block: Option<(u32, Vec<Section>)>, offset: Option<usize>) {
+    let Some((id, sections)) = block else {
+      self.block = None;
+      self.sections.clear();
+      self.rows.clear();
+      return;
+    };
+    if self.block != Some(id) {
+      self.block = Some(id);
+      self.selected.clear();
+      self.scroll = 0;
+    }
+    self.sections = sections;
+    if !self.focused {
+      self.selected = offset.map_or(vec![], |offset| self.path_to(offset));
+      // Open the groups above the selection so it can be seen
+      let names = self.names(&self.selected);
+      for depth in 1..names.len() {
+        self.expanded.insert(names[..depth].to_vec());
+      }
+    }
+    self.rebuild();
+  }
+
+  fn path_to(&self, offset: usize) -> Vec<usize> {
+    let mut path = vec![];
+    let mut sections = &self.sections;
+    while let Some(i) = sections
+      .iter()
+      .position(|s| s.offset <= offset && offset < s.offset + s.length)
+    {
+      path.push(i);
+      sections = &sections[i].children;
+    }
+    path
+  }
+
+  fn names(&self, path: &[usize]) -> Vec<String> {
+    let mut names = vec![];
+    let mut sections = &self.sections;
+    for &i in path {
+      names.push(sections[i].name.clone());
+      sections = &sections[i].children;
+    }
+    names
+  }
+
+  fn rebuild(&mut self) {
+    let mut rows = vec![];
+    self.add_rows(&mut rows, &self.sections, &[], &[]);
+    self.rows = rows;
+  }
+
+  fn add_rows(&self, rows: &mut Vec<Row>, sections: &[Section], path: &[usize], names: &[String]) {
+    for (i, section) in sections.iter().enumerate() {
+      let mut path = path.to_vec();
+      path.push(i);
+      let mut names = names.to_vec();
+      names.push(section.name.clone());
+      rows.push(Row {
+        depth: path.len() - 1,
+        path: path.clone(),
+        names: names.clone(),
+        group: !section.children.is_empty(),
+        description: section.description(),
+        range: section.offset..section.offset + section.length,
+      });
+      if self.expanded.contains(&names) {
+        self.add_rows(rows, &section.children, &path, &names);
+      }
+    }
+  }
+
+  // Index of the selected row, or of its nearest visible parent when it is inside a closed group
+  fn selected_row(&self) -> Option<usize> {
+    (1..=self.selected.len()).rev().find_map(|depth| {
+      self
+        .rows
+        .iter()
+        .position(|r| r.path == self.selected[..depth])
+    })
+  }
+
+  fn select_row(&mut self, row: usize) {
+    if let Some(row) = self.rows.get(row) {
+      self.selected = row.path.clone();
+    }
+  }
+
+  pub fn down(&mut self) {
+    match self.selected_row() {
+      Some(row) => self.select_row(row + 1),
+      None => self.select_row(0),
+    }
+  }
+
+  pub fn up(&mut self) {
+    if let Some(row) = self.selected_row() {
+      self.select_row(row.saturating_sub(1));
+    }
+  }
+
+  // Opens the selected group, or moves into it when it is already open
+  pub fn expand(&mut self) {
+    let Some(row) = self.selected_row() else {
+      return;
+    };
+    let Row {
+      path, names, group, ..
+    } = &self.rows[row];
+    if !group {
+      return;
+    }
+    if self.expanded.insert(names.clone()) {
+      self.rebuild();
+    } else {
+      self.selected = [path, &[0][..]].concat();
+    }
+  }
+
+  // Closes the selected group, or moves to the group holding the selected section
+  pub fn collapse(&mut self) {
+    let Some(row) = self.selected_row() else {
+      return;
+    };
+    let Row {
+      path, names, group, ..
+    } = &self.rows[row];
+    if *group && self.expanded.remove(names) {
+      self.selected = path.clone();
+      self.rebuild();
+    } else if path.len() > 1 {
+      self.selected = path[..path.len() - 1].to_vec();
+    }
+  }
+
+  pub fn toggle(&mut self) {
+    let Some(row) = self.selected_row() else {
+      return;
+    };
+    let Row { names, group, .. } = &self.rows[row];
+    if *group && !self.expanded.remove(names) {
+      self.expanded.insert(names.clone());
+    }
+    self.rebuild();
+  }
+
+  // Block and bytes of the selected section
+  pub fn highlight(&self) -> Option<(u32, Range<usize>)> {
+    let row = self.rows.get(self.selected_row()?)?;
+    Some((self.block?, row.range.clone()))
+  }
+
+  pub fn draw(&mut self, area: Rect, buf: &mut Buffer) {
+    let mut block = Block::bordered().title("Sections");
+    if self.focused {
+      block = block.border_style(Style::new().bold());
+    }
+    let inner = block.inner(area);
+    block.render(area, buf);
+    let height = inner.height as usize;
+
+    let selected = self.selected_row();
+    if let Some(row) = selected {
+      // Keep the selection in view
+      if row < self.scroll {
+        self.scroll = row;
+      } else if row >= self.scroll + height {
+        self.scroll = row + 1 - height;
+      }
+    }
+    self.scroll = self.scroll.min(self.rows.len().saturating_sub(height));
+
+    for (i, row) in self.rows.iter().enumerate().skip(self.scroll).take(height) {
+      let marker = if !row.group {
+        "  "
+      } else if self.expanded.contains(&row.names) {
+        "▾ "
+      } else {
+        "▸ "
+      };
+      let style = match selected {
+        Some(s) if s == i && self.focused => Style::new().reversed(),
+        Some(s) if s == i => Style::new().bold(),
+        _ => Style::new(),
+      };
+      Line::styled("  ".repeat(row.depth) + marker + &row.description, style).render(
+        Rect {
+          y: inner.y + (i - self.scroll) as u16,
+          height: 1,
+          ..inner
+        },
+        buf,
+      );
+    }
+  }
+}
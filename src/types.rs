@@ -29,6 +29,11 @@ pub enum BlockTypes {
   CustomBlockRewritersShouldNotCopy = 0x40000BAD,
   SectionHeaderBlock = 0x0A0D0D0A,
   Unknown,
+  // Classic libpcap structures, presented as blocks
+  PcapFileHeader,
+  PcapRecord,
+  // Bytes skipped while resynchronizing after a corrupt block
+  UnparsedGap,
 }
 
 pub fn block_type_str(block_type: &BlockTypes) -> String {
@@ -65,6 +70,9 @@ pub fn block_type_str(block_type: &BlockTypes) -> String {
       "Custom Block that rewriters should not copy into new files".to_owned()
     }
     BlockTypes::SectionHeaderBlock => "Section Header Block".to_owned(),
+    BlockTypes::PcapFileHeader => "Pcap File Header".to_owned(),
+    BlockTypes::PcapRecord => "Pcap Record".to_owned(),
+    BlockTypes::UnparsedGap => "Unparsed Gap".to_owned(),
     _ => "Unknown".to_owned(),
   }
 }
@@ -1,3 +1,39 @@
-pub fn div_ceil(a: u16, b: u16) -> u16 {
-  (a + b - 1) / b
+pub fn read_u16(data: &[u8], little_endian: bool) -> u16 {
+  let bytes: [u8; 2] = data[..2].try_into().unwrap();
+  if little_endian {
+    u16::from_le_bytes(bytes)
+  } else {
+    u16::from_be_bytes(bytes)
+  }
+}
+
+pub fn read_u32(data: &[u8], little_endian: bool) -> u32 {
+  let bytes: [u8; 4] = data[..4].try_into().unwrap();
+  if little_endian {
+    u32::from_le_bytes(bytes)
+  } else {
+    u32::from_be_bytes(bytes)
+  }
+}
+
+pub fn read_u64(data: &[u8], little_endian: bool) -> u64 {
+  let bytes: [u8; 8] = data[..8].try_into().unwrap();
+  if little_endian {
+    u64::from_le_bytes(bytes)
+  } else {
+    u64::from_be_bytes(bytes)
+  }
+}
+
+// Reads an unsigned integer of up to 8 bytes, e.g. a user defined field from data.json
+pub fn read_uint(data: &[u8], little_endian: bool) -> u64 {
+  let data = &data[..data.len().min(8)];
+  let mut padded: [u8; 8] = [0; 8];
+  if little_endian {
+    padded[..data.len()].copy_from_slice(data);
+    u64::from_le_bytes(padded)
+  } else {
+    padded[8 - data.len()..].copy_from_slice(data);
+    u64::from_be_bytes(padded)
+  }
 }